@@ -134,6 +134,18 @@ mod c {
         let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
         let target_vendor = env::var("CARGO_CFG_TARGET_VENDOR").unwrap();
         let mut consider_float_intrinsics = true;
+        // Mirrors the `cfg` of the intrinsics in `src/float/extended.rs`
+        let has_x87_intrinsics = (target_arch == "x86_64"
+            || (target_arch == "x86" && target_vendor != "apple"))
+            && target_os != "windows"
+            && target_os != "uefi"
+            && !cfg!(feature = "no-asm");
+        // Mirrors the `cfg` of `mod aarch64` in `src/lib.rs`, so that the outline atomics of
+        // compiler-rt and the Rust ones are never both linked
+        let has_aarch64_outline_atomics = target_arch == "aarch64"
+            && target_vendor != "apple"
+            && target_os != "windows"
+            && !cfg!(feature = "no-asm");
         let cfg = &mut cc::Build::new();
 
         // AArch64 GCCs exit with an error condition when they encounter any kind of floating point
@@ -178,78 +190,40 @@ mod c {
 
         let mut sources = Sources::new();
         sources.extend(&[
-            ("__absvdi2", "absvdi2.c"),
-            ("__absvsi2", "absvsi2.c"),
-            ("__addvdi3", "addvdi3.c"),
-            ("__addvsi3", "addvsi3.c"),
             ("apple_versioning", "apple_versioning.c"),
             ("__clzdi2", "clzdi2.c"),
             ("__clzsi2", "clzsi2.c"),
-            ("__cmpdi2", "cmpdi2.c"),
-            ("__ctzdi2", "ctzdi2.c"),
-            ("__ctzsi2", "ctzsi2.c"),
-            ("__int_util", "int_util.c"),
-            ("__mulvdi3", "mulvdi3.c"),
-            ("__mulvsi3", "mulvsi3.c"),
-            ("__negdi2", "negdi2.c"),
-            ("__negvdi2", "negvdi2.c"),
-            ("__negvsi2", "negvsi2.c"),
-            ("__paritydi2", "paritydi2.c"),
-            ("__paritysi2", "paritysi2.c"),
-            ("__popcountdi2", "popcountdi2.c"),
-            ("__popcountsi2", "popcountsi2.c"),
-            ("__subvdi3", "subvdi3.c"),
-            ("__subvsi3", "subvsi3.c"),
-            ("__ucmpdi2", "ucmpdi2.c"),
         ]);
 
         if consider_float_intrinsics {
-            sources.extend(&[
-                ("__divdc3", "divdc3.c"),
-                ("__divsc3", "divsc3.c"),
-                ("__divxc3", "divxc3.c"),
-                ("__extendhfsf2", "extendhfsf2.c"),
-                ("__muldc3", "muldc3.c"),
-                ("__mulsc3", "mulsc3.c"),
-                ("__mulxc3", "mulxc3.c"),
-                ("__negdf2", "negdf2.c"),
-                ("__negsf2", "negsf2.c"),
-                ("__powixf2", "powixf2.c"),
-                ("__truncdfhf2", "truncdfhf2.c"),
-                ("__truncdfsf2", "truncdfsf2.c"),
-                ("__truncsfhf2", "truncsfhf2.c"),
-            ]);
-        }
+            sources.extend(&[("__negdf2", "negdf2.c"), ("__negsf2", "negsf2.c")]);
+
+            // On these targets `long double` is the x87 format, which the Rust versions handle,
+            // see `src/float/extended.rs`.
+            if !has_x87_intrinsics {
+                sources.extend(&[
+                    ("__divxc3", "divxc3.c"),
+                    ("__mulxc3", "mulxc3.c"),
+                    ("__powixf2", "powixf2.c"),
+                ]);
+            }
 
-        // When compiling in rustbuild (the rust-lang/rust repo) this library
-        // also needs to satisfy intrinsics that jemalloc or C in general may
-        // need, so include a few more that aren't typically needed by
-        // LLVM/Rust.
-        if cfg!(feature = "rustbuild") {
-            sources.extend(&[("__ffsdi2", "ffsdi2.c")]);
+            // The Rust versions of these are only built where `_Complex` has the C ABI of a
+            // struct, see `src/float/complex.rs`.
+            if !["x86_64", "aarch64", "arm", "riscv32", "riscv64"].contains(&target_arch.as_str()) {
+                sources.extend(&[
+                    ("__divdc3", "divdc3.c"),
+                    ("__divsc3", "divsc3.c"),
+                    ("__muldc3", "muldc3.c"),
+                    ("__mulsc3", "mulsc3.c"),
+                ]);
+            }
         }
 
         // On iOS and 32-bit OSX these are all just empty intrinsics, no need to
         // include them.
         if target_os != "ios" && (target_vendor != "apple" || target_arch != "x86") {
-            sources.extend(&[
-                ("__absvti2", "absvti2.c"),
-                ("__addvti3", "addvti3.c"),
-                ("__clzti2", "clzti2.c"),
-                ("__cmpti2", "cmpti2.c"),
-                ("__ctzti2", "ctzti2.c"),
-                ("__ffsti2", "ffsti2.c"),
-                ("__mulvti3", "mulvti3.c"),
-                ("__negti2", "negti2.c"),
-                ("__parityti2", "parityti2.c"),
-                ("__popcountti2", "popcountti2.c"),
-                ("__subvti3", "subvti3.c"),
-                ("__ucmpti2", "ucmpti2.c"),
-            ]);
-
-            if consider_float_intrinsics {
-                sources.extend(&[("__negvti2", "negvti2.c")]);
-            }
+            sources.extend(&[("__clzti2", "clzti2.c")]);
         }
 
         if target_vendor == "apple" {
@@ -280,12 +254,25 @@ mod c {
                 if target_arch == "x86_64" {
                     sources.extend(&[
                         ("__floatdisf", "x86_64/floatdisf.c"),
-                        ("__floatdixf", "x86_64/floatdixf.c"),
                         ("__floatundidf", "x86_64/floatundidf.S"),
                         ("__floatundisf", "x86_64/floatundisf.S"),
+                    ]);
+                }
+            }
+
+            if !has_x87_intrinsics {
+                if target_arch == "x86_64" && target_os != "windows" {
+                    sources.extend(&[
+                        ("__floatdixf", "x86_64/floatdixf.c"),
                         ("__floatundixf", "x86_64/floatundixf.S"),
                     ]);
                 }
+                if target_arch == "x86" {
+                    sources.extend(&[
+                        ("__floatdixf", "i386/floatdixf.S"),
+                        ("__floatundixf", "i386/floatundixf.S"),
+                    ]);
+                }
             }
 
             if target_arch == "x86" {
@@ -295,10 +282,8 @@ mod c {
                     ("__divdi3", "i386/divdi3.S"),
                     ("__floatdidf", "i386/floatdidf.S"),
                     ("__floatdisf", "i386/floatdisf.S"),
-                    ("__floatdixf", "i386/floatdixf.S"),
                     ("__floatundidf", "i386/floatundidf.S"),
                     ("__floatundisf", "i386/floatundisf.S"),
-                    ("__floatundixf", "i386/floatundixf.S"),
                     ("__lshrdi3", "i386/lshrdi3.S"),
                     ("__moddi3", "i386/moddi3.S"),
                     ("__muldi3", "i386/muldi3.S"),
@@ -310,11 +295,6 @@ mod c {
 
         if target_arch == "arm" && target_os != "ios" && target_env != "msvc" {
             sources.extend(&[
-                ("__aeabi_div0", "arm/aeabi_div0.c"),
-                ("__aeabi_drsub", "arm/aeabi_drsub.c"),
-                ("__aeabi_frsub", "arm/aeabi_frsub.c"),
-                ("__bswapdi2", "arm/bswapdi2.S"),
-                ("__bswapsi2", "arm/bswapsi2.S"),
                 ("__clzdi2", "arm/clzdi2.S"),
                 ("__clzsi2", "arm/clzsi2.S"),
                 ("__divmodsi4", "arm/divmodsi4.S"),
@@ -334,16 +314,17 @@ mod c {
                 sources.extend(&[("__clear_cache", "clear_cache.c")]);
             }
 
-            // First of all aeabi_cdcmp and aeabi_cfcmp are never called by LLVM.
-            // Second are little-endian only, so build fail on big-endian targets.
-            // Temporally workaround: exclude these files for big-endian targets.
-            if !llvm_target[0].starts_with("thumbeb") && !llvm_target[0].starts_with("armeb") {
-                sources.extend(&[
-                    ("__aeabi_cdcmp", "arm/aeabi_cdcmp.S"),
-                    ("__aeabi_cdcmpeq_check_nan", "arm/aeabi_cdcmpeq_check_nan.c"),
-                    ("__aeabi_cfcmp", "arm/aeabi_cfcmp.S"),
-                    ("__aeabi_cfcmpeq_check_nan", "arm/aeabi_cfcmpeq_check_nan.c"),
-                ]);
+            // The Rust versions of these are in `src/arm.rs`, which is not built without `asm!`.
+            if cfg!(feature = "no-asm") {
+                sources.extend(&[("__aeabi_div0", "arm/aeabi_div0.c")]);
+
+                // The compiler-rt versions of these are little-endian only.
+                if !llvm_target[0].starts_with("thumbeb") && !llvm_target[0].starts_with("armeb") {
+                    sources.extend(&[
+                        ("__aeabi_cdcmp", "arm/aeabi_cdcmp.S"),
+                        ("__aeabi_cfcmp", "arm/aeabi_cfcmp.S"),
+                    ]);
+                }
             }
         }
 
@@ -373,32 +354,9 @@ mod c {
         }
 
         if llvm_target.last().unwrap().ends_with("eabihf") {
-            if !llvm_target[0].starts_with("thumbv7em")
-                && !llvm_target[0].starts_with("thumbv8m.main")
-            {
-                // The FPU option chosen for these architectures in cc-rs, ie:
-                //     -mfpu=fpv4-sp-d16 for thumbv7em
-                //     -mfpu=fpv5-sp-d16 for thumbv8m.main
-                // do not support double precision floating points conversions so the files
-                // that include such instructions are not included for these targets.
-                sources.extend(&[
-                    ("__fixdfsivfp", "arm/fixdfsivfp.S"),
-                    ("__fixunsdfsivfp", "arm/fixunsdfsivfp.S"),
-                    ("__floatsidfvfp", "arm/floatsidfvfp.S"),
-                    ("__floatunssidfvfp", "arm/floatunssidfvfp.S"),
-                ]);
-            }
-
             sources.extend(&[
-                ("__fixsfsivfp", "arm/fixsfsivfp.S"),
-                ("__fixunssfsivfp", "arm/fixunssfsivfp.S"),
-                ("__floatsisfvfp", "arm/floatsisfvfp.S"),
-                ("__floatunssisfvfp", "arm/floatunssisfvfp.S"),
-                ("__floatunssisfvfp", "arm/floatunssisfvfp.S"),
                 ("__restore_vfp_d8_d15_regs", "arm/restore_vfp_d8_d15_regs.S"),
                 ("__save_vfp_d8_d15_regs", "arm/save_vfp_d8_d15_regs.S"),
-                ("__negdf2vfp", "arm/negdf2vfp.S"),
-                ("__negsf2vfp", "arm/negsf2vfp.S"),
             ]);
         }
 
@@ -426,35 +384,18 @@ mod c {
             }
 
             if target_env == "musl" {
-                sources.extend(&[
-                    ("__addtf3", "addtf3.c"),
-                    ("__multf3", "multf3.c"),
-                    ("__subtf3", "subtf3.c"),
-                    ("__divtf3", "divtf3.c"),
-                    ("__powitf2", "powitf2.c"),
-                    ("__fe_getround", "fp_mode.c"),
-                    ("__fe_raise_inexact", "fp_mode.c"),
-                ]);
+                sources.extend(&[("__powitf2", "powitf2.c")]);
             }
         }
 
-        if target_arch == "mips" {
-            sources.extend(&[("__bswapsi2", "bswapsi2.c")]);
-        }
-
         if target_arch == "mips64" {
             sources.extend(&[
                 ("__extenddftf2", "extenddftf2.c"),
                 ("__netf2", "comparetf2.c"),
-                ("__addtf3", "addtf3.c"),
-                ("__multf3", "multf3.c"),
-                ("__subtf3", "subtf3.c"),
                 ("__fixtfsi", "fixtfsi.c"),
                 ("__floatsitf", "floatsitf.c"),
                 ("__fixunstfsi", "fixunstfsi.c"),
                 ("__floatunsitf", "floatunsitf.c"),
-                ("__fe_getround", "fp_mode.c"),
-                ("__divtf3", "divtf3.c"),
                 ("__trunctfdf2", "trunctfdf2.c"),
             ]);
         }
@@ -494,10 +435,10 @@ mod c {
         // use of that macro in lib/builtins/int_util.h in compiler-rt.
         cfg.flag_if_supported(&format!("-ffile-prefix-map={}=.", root.display()));
 
-        // Include out-of-line atomics for aarch64, which are all generated by supplying different
-        // sets of flags to the same source file.
+        // Include out-of-line atomics for aarch64 where the Rust ones are not available, which are
+        // all generated by supplying different sets of flags to the same source file.
         let src_dir = root.join("lib/builtins");
-        if target_arch == "aarch64" {
+        if target_arch == "aarch64" && !has_aarch64_outline_atomics {
             // See below for why we're building these as separate libraries.
             build_aarch64_out_of_line_atomics_libraries(&src_dir, cfg);
 
@@ -14,6 +14,20 @@ else
     $run --features c --release
     $run --features no-asm
     $run --features no-asm --release
+    $run --features fenv
+    $run --features fenv --release
+    $run --features atomic
+    $run --features atomic --release
+    $run --features atomic,critical-section
+    $run --features atomic,critical-section --release
+
+    # The outline atomics pick their implementation at startup, so test both of them
+    case $1 in
+        aarch64-unknown-linux-*)
+            QEMU_CPU=cortex-a57 $run --test outline_atomics
+            QEMU_CPU=max $run --test outline_atomics
+            ;;
+    esac
 fi
 
 cargo build --target $1
@@ -25,6 +39,9 @@ cargo build --target $1 --release --features no-asm
 
 PREFIX=$(echo $1 | sed -e 's/unknown-//')-
 case $1 in
+    armv5te-*)
+        PREFIX=arm-linux-gnueabi-
+        ;;
     armv7-*)
         PREFIX=arm-linux-gnueabihf-
         ;;
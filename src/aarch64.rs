@@ -0,0 +1,360 @@
+// The outline atomics that LLVM calls instead of inlining atomic operations when targeting
+// `+outline-atomics`. Each one uses the LSE (ARMv8.1 Large System Extensions) instruction when
+// the CPU supports it, and falls back to a load-exclusive/store-exclusive loop otherwise.
+//
+// NOTE These functions are implemented using assembly because callers may assume that they only
+// clobber x15-x17 and the flags, like the compiler-rt and libgcc versions.
+//
+// The arguments and the result follow the C ABI: for example `__aarch64_cas4_acq(expected: u32,
+// desired: u32, ptr: *mut u32) -> u32` and `__aarch64_ldadd8_rel(val: u64, ptr: *mut u64) ->
+// u64`, where the result is always the previous value in memory.
+
+use core::sync::atomic::AtomicBool;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use core::sync::atomic::Ordering;
+
+public_test_dep! {
+/// Whether the LSE instructions are available, which is only detected at startup on Linux and
+/// Android. Everywhere else the LL/SC fallback is used. The helpers read it with `ldrb`, which is
+/// a relaxed atomic load.
+pub(crate) static HAVE_LSE_ATOMICS: AtomicBool = AtomicBool::new(false);
+}
+
+// The detection runs as a constructor, the same way the standard library initializes itself on
+// Linux. `#[used]` keeps the pointer in `.init_array`, which the linker keeps as a root even with
+// `--gc-sections`. Because the helpers reference `HAVE_LSE_ATOMICS` from this module, the object
+// with the constructor is linked whenever they are. The priority is the one of `cpu_model.c` in
+// compiler-rt, so the flag is set before C and C++ constructors that might use atomics run.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[used]
+#[link_section = ".init_array.00090"]
+static INIT_HAVE_LSE_ATOMICS: extern "C" fn() = {
+    extern "C" fn init_have_lse_atomics() {
+        extern "C" {
+            fn getauxval(type_: u64) -> u64;
+        }
+        const AT_HWCAP: u64 = 16;
+        const HWCAP_ATOMICS: u64 = 1 << 8;
+
+        let hwcap = unsafe { getauxval(AT_HWCAP) };
+        // Constructors run before any other thread is started
+        HAVE_LSE_ATOMICS.store(hwcap & HWCAP_ATOMICS != 0, Ordering::Relaxed);
+    }
+    init_have_lse_atomics
+};
+
+// Every helper comes in four memory orderings, which select the acquire (`a`) and release (`l`)
+// variants of the instructions.
+macro_rules! orderings {
+    ($helper:ident!($($arg:tt)*), $relax:ident, $acq:ident, $rel:ident, $acq_rel:ident) => {
+        $helper!($relax, "", "", $($arg)*);
+        $helper!($acq, "a", "", $($arg)*);
+        $helper!($rel, "", "l", $($arg)*);
+        $helper!($acq_rel, "a", "l", $($arg)*);
+    };
+}
+
+// The LSE instructions are only reached after checking `HAVE_LSE_ATOMICS`, so the assembler is
+// told to accept them regardless of the target features.
+//
+// `$sz` is the instruction suffix for the size, `$r` the register prefix, and `$ext` the
+// extension needed to compare only the low bits of the expected value.
+macro_rules! cas {
+    ($name:ident, $a:literal, $l:literal, $sz:literal, $r:literal, $ext:literal) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "adrp x16, {have_lse}",
+                "ldrb w16, [x16, :lo12:{have_lse}]",
+                "cbz w16, 8f",
+                ".arch_extension lse",
+                concat!("cas", $a, $l, $sz, " ", $r, "0, ", $r, "1, [x2]"),
+                "ret",
+                "8:",
+                concat!("mov ", $r, "16, ", $r, "0"),
+                "0:",
+                concat!("ld", $a, "xr", $sz, " ", $r, "0, [x2]"),
+                concat!("cmp ", $r, "0, ", $r, "16", $ext),
+                "bne 1f",
+                concat!("st", $l, "xr", $sz, " w17, ", $r, "1, [x2]"),
+                "cbnz w17, 0b",
+                "1:",
+                "ret",
+                have_lse = sym HAVE_LSE_ATOMICS,
+                options(noreturn)
+            );
+        }
+    };
+}
+
+macro_rules! cas16 {
+    ($name:ident, $a:literal, $l:literal,) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "adrp x16, {have_lse}",
+                "ldrb w16, [x16, :lo12:{have_lse}]",
+                "cbz w16, 8f",
+                ".arch_extension lse",
+                concat!("casp", $a, $l, " x0, x1, x2, x3, [x4]"),
+                "ret",
+                "8:",
+                "mov x16, x0",
+                "mov x17, x1",
+                "0:",
+                concat!("ld", $a, "xp x0, x1, [x4]"),
+                "cmp x0, x16",
+                "ccmp x1, x17, #0, eq",
+                "bne 1f",
+                concat!("st", $l, "xp w15, x2, x3, [x4]"),
+                "cbnz w15, 0b",
+                "1:",
+                "ret",
+                have_lse = sym HAVE_LSE_ATOMICS,
+                options(noreturn)
+            );
+        }
+    };
+}
+
+macro_rules! swp {
+    ($name:ident, $a:literal, $l:literal, $sz:literal, $r:literal) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "adrp x16, {have_lse}",
+                "ldrb w16, [x16, :lo12:{have_lse}]",
+                "cbz w16, 8f",
+                ".arch_extension lse",
+                concat!("swp", $a, $l, $sz, " ", $r, "0, ", $r, "0, [x1]"),
+                "ret",
+                "8:",
+                concat!("mov ", $r, "16, ", $r, "0"),
+                "0:",
+                concat!("ld", $a, "xr", $sz, " ", $r, "0, [x1]"),
+                concat!("st", $l, "xr", $sz, " w17, ", $r, "16, [x1]"),
+                "cbnz w17, 0b",
+                "ret",
+                have_lse = sym HAVE_LSE_ATOMICS,
+                options(noreturn)
+            );
+        }
+    };
+}
+
+// `$op` is the LSE instruction and `$llsc_op` the equivalent data-processing instruction, which
+// combines the previous value with the argument.
+macro_rules! ldop {
+    (
+        $name:ident, $a:literal, $l:literal,
+        $op:literal, $llsc_op:literal, $sz:literal, $r:literal
+    ) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "adrp x16, {have_lse}",
+                "ldrb w16, [x16, :lo12:{have_lse}]",
+                "cbz w16, 8f",
+                ".arch_extension lse",
+                concat!($op, $a, $l, $sz, " ", $r, "0, ", $r, "0, [x1]"),
+                "ret",
+                "8:",
+                concat!("mov ", $r, "16, ", $r, "0"),
+                "0:",
+                concat!("ld", $a, "xr", $sz, " ", $r, "0, [x1]"),
+                concat!($llsc_op, " ", $r, "17, ", $r, "0, ", $r, "16"),
+                concat!("st", $l, "xr", $sz, " w15, ", $r, "17, [x1]"),
+                "cbnz w15, 0b",
+                "ret",
+                have_lse = sym HAVE_LSE_ATOMICS,
+                options(noreturn)
+            );
+        }
+    };
+}
+
+orderings!(
+    cas!("b", "w", ", uxtb"),
+    __aarch64_cas1_relax,
+    __aarch64_cas1_acq,
+    __aarch64_cas1_rel,
+    __aarch64_cas1_acq_rel
+);
+orderings!(
+    cas!("h", "w", ", uxth"),
+    __aarch64_cas2_relax,
+    __aarch64_cas2_acq,
+    __aarch64_cas2_rel,
+    __aarch64_cas2_acq_rel
+);
+orderings!(
+    cas!("", "w", ""),
+    __aarch64_cas4_relax,
+    __aarch64_cas4_acq,
+    __aarch64_cas4_rel,
+    __aarch64_cas4_acq_rel
+);
+orderings!(
+    cas!("", "x", ""),
+    __aarch64_cas8_relax,
+    __aarch64_cas8_acq,
+    __aarch64_cas8_rel,
+    __aarch64_cas8_acq_rel
+);
+orderings!(
+    cas16!(),
+    __aarch64_cas16_relax,
+    __aarch64_cas16_acq,
+    __aarch64_cas16_rel,
+    __aarch64_cas16_acq_rel
+);
+
+orderings!(
+    swp!("b", "w"),
+    __aarch64_swp1_relax,
+    __aarch64_swp1_acq,
+    __aarch64_swp1_rel,
+    __aarch64_swp1_acq_rel
+);
+orderings!(
+    swp!("h", "w"),
+    __aarch64_swp2_relax,
+    __aarch64_swp2_acq,
+    __aarch64_swp2_rel,
+    __aarch64_swp2_acq_rel
+);
+orderings!(
+    swp!("", "w"),
+    __aarch64_swp4_relax,
+    __aarch64_swp4_acq,
+    __aarch64_swp4_rel,
+    __aarch64_swp4_acq_rel
+);
+orderings!(
+    swp!("", "x"),
+    __aarch64_swp8_relax,
+    __aarch64_swp8_acq,
+    __aarch64_swp8_rel,
+    __aarch64_swp8_acq_rel
+);
+
+orderings!(
+    ldop!("ldadd", "add", "b", "w"),
+    __aarch64_ldadd1_relax,
+    __aarch64_ldadd1_acq,
+    __aarch64_ldadd1_rel,
+    __aarch64_ldadd1_acq_rel
+);
+orderings!(
+    ldop!("ldadd", "add", "h", "w"),
+    __aarch64_ldadd2_relax,
+    __aarch64_ldadd2_acq,
+    __aarch64_ldadd2_rel,
+    __aarch64_ldadd2_acq_rel
+);
+orderings!(
+    ldop!("ldadd", "add", "", "w"),
+    __aarch64_ldadd4_relax,
+    __aarch64_ldadd4_acq,
+    __aarch64_ldadd4_rel,
+    __aarch64_ldadd4_acq_rel
+);
+orderings!(
+    ldop!("ldadd", "add", "", "x"),
+    __aarch64_ldadd8_relax,
+    __aarch64_ldadd8_acq,
+    __aarch64_ldadd8_rel,
+    __aarch64_ldadd8_acq_rel
+);
+
+// `ldclr` clears the bits that are set in the argument
+orderings!(
+    ldop!("ldclr", "bic", "b", "w"),
+    __aarch64_ldclr1_relax,
+    __aarch64_ldclr1_acq,
+    __aarch64_ldclr1_rel,
+    __aarch64_ldclr1_acq_rel
+);
+orderings!(
+    ldop!("ldclr", "bic", "h", "w"),
+    __aarch64_ldclr2_relax,
+    __aarch64_ldclr2_acq,
+    __aarch64_ldclr2_rel,
+    __aarch64_ldclr2_acq_rel
+);
+orderings!(
+    ldop!("ldclr", "bic", "", "w"),
+    __aarch64_ldclr4_relax,
+    __aarch64_ldclr4_acq,
+    __aarch64_ldclr4_rel,
+    __aarch64_ldclr4_acq_rel
+);
+orderings!(
+    ldop!("ldclr", "bic", "", "x"),
+    __aarch64_ldclr8_relax,
+    __aarch64_ldclr8_acq,
+    __aarch64_ldclr8_rel,
+    __aarch64_ldclr8_acq_rel
+);
+
+orderings!(
+    ldop!("ldeor", "eor", "b", "w"),
+    __aarch64_ldeor1_relax,
+    __aarch64_ldeor1_acq,
+    __aarch64_ldeor1_rel,
+    __aarch64_ldeor1_acq_rel
+);
+orderings!(
+    ldop!("ldeor", "eor", "h", "w"),
+    __aarch64_ldeor2_relax,
+    __aarch64_ldeor2_acq,
+    __aarch64_ldeor2_rel,
+    __aarch64_ldeor2_acq_rel
+);
+orderings!(
+    ldop!("ldeor", "eor", "", "w"),
+    __aarch64_ldeor4_relax,
+    __aarch64_ldeor4_acq,
+    __aarch64_ldeor4_rel,
+    __aarch64_ldeor4_acq_rel
+);
+orderings!(
+    ldop!("ldeor", "eor", "", "x"),
+    __aarch64_ldeor8_relax,
+    __aarch64_ldeor8_acq,
+    __aarch64_ldeor8_rel,
+    __aarch64_ldeor8_acq_rel
+);
+
+orderings!(
+    ldop!("ldset", "orr", "b", "w"),
+    __aarch64_ldset1_relax,
+    __aarch64_ldset1_acq,
+    __aarch64_ldset1_rel,
+    __aarch64_ldset1_acq_rel
+);
+orderings!(
+    ldop!("ldset", "orr", "h", "w"),
+    __aarch64_ldset2_relax,
+    __aarch64_ldset2_acq,
+    __aarch64_ldset2_rel,
+    __aarch64_ldset2_acq_rel
+);
+orderings!(
+    ldop!("ldset", "orr", "", "w"),
+    __aarch64_ldset4_relax,
+    __aarch64_ldset4_acq,
+    __aarch64_ldset4_rel,
+    __aarch64_ldset4_acq_rel
+);
+orderings!(
+    ldop!("ldset", "orr", "", "x"),
+    __aarch64_ldset8_relax,
+    __aarch64_ldset8_acq,
+    __aarch64_ldset8_rel,
+    __aarch64_ldset8_acq_rel
+);
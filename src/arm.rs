@@ -141,6 +141,41 @@ pub unsafe extern "C" fn __aeabi_ldivmod() {
     );
 }
 
+// NOTE The flag-returning comparisons return their result in the Z and C flags, and have to preserve
+// every core register except ip and lr. They save the arguments around a call to their Rust core,
+// which returns 0, 1 or 2 for "less", "equal" and "greater or unordered", and `cmp r0, #1` turns
+// that into the flags. r4 is only saved to keep the stack 8-byte aligned, and all of the
+// instructions exist in the ARM, Thumb-1 and Thumb-2 instruction sets. The `*cmpeq` variants only
+// differ from `*cmple` in not signaling for quiet NaNs, which the cores never do anyway.
+macro_rules! flag_cmp {
+    ($($name:ident, $core:path;)*) => {
+        $(
+            #[cfg(not(target_os = "ios"))]
+            #[naked]
+            #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+            pub unsafe extern "C" fn $name() {
+                asm!(
+                    "push {{r0-r4, lr}}",
+                    "bl {core}",
+                    "cmp r0, #1",
+                    "pop {{r0-r4, pc}}",
+                    core = sym $core,
+                    options(noreturn)
+                );
+            }
+        )*
+    };
+}
+
+flag_cmp! {
+    __aeabi_cfcmpeq, ::float::cmp::cfcmple;
+    __aeabi_cfcmple, ::float::cmp::cfcmple;
+    __aeabi_cfrcmple, ::float::cmp::cfrcmple;
+    __aeabi_cdcmpeq, ::float::cmp::cdcmple;
+    __aeabi_cdcmple, ::float::cmp::cdcmple;
+    __aeabi_cdrcmple, ::float::cmp::cdrcmple;
+}
+
 // The following functions use weak linkage to allow users to override
 // with custom implementation.
 // FIXME: The `*4` and `*8` variants should be defined as aliases.
@@ -251,3 +286,51 @@ pub unsafe extern "aapcs" fn __aeabi_memclr4(dest: *mut u8, n: usize) {
 pub unsafe extern "aapcs" fn __aeabi_memclr8(dest: *mut u8, n: usize) {
     __aeabi_memset4(dest, n, 0);
 }
+
+// The remaining helpers of the run-time ABI, which have plain AAPCS signatures. They are weak as
+// well, the division by zero handlers in particular are meant to be replaced by users that want to
+// trap.
+
+#[cfg(not(target_os = "ios"))]
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[linkage = "weak"]
+pub extern "aapcs" fn __aeabi_idiv0(return_value: i32) -> i32 {
+    return_value
+}
+
+#[cfg(not(target_os = "ios"))]
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[linkage = "weak"]
+pub extern "aapcs" fn __aeabi_ldiv0(return_value: i64) -> i64 {
+    return_value
+}
+
+#[cfg(not(target_os = "ios"))]
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[linkage = "weak"]
+pub unsafe extern "aapcs" fn __aeabi_uread4(address: *const i32) -> i32 {
+    address.read_unaligned()
+}
+
+#[cfg(not(target_os = "ios"))]
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[linkage = "weak"]
+pub unsafe extern "aapcs" fn __aeabi_uread8(address: *const i64) -> i64 {
+    address.read_unaligned()
+}
+
+#[cfg(not(target_os = "ios"))]
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[linkage = "weak"]
+pub unsafe extern "aapcs" fn __aeabi_uwrite4(value: i32, address: *mut i32) -> i32 {
+    address.write_unaligned(value);
+    value
+}
+
+#[cfg(not(target_os = "ios"))]
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[linkage = "weak"]
+pub unsafe extern "aapcs" fn __aeabi_uwrite8(value: i64, address: *mut i64) -> i64 {
+    address.write_unaligned(value);
+    value
+}
@@ -1,5 +1,6 @@
 use core::intrinsics;
 use core::mem;
+use core::ptr;
 
 // Kernel-provided user-mode helper functions:
 // https://www.kernel.org/doc/Documentation/arm/kernel_user_helpers.txt
@@ -11,6 +12,18 @@ unsafe fn __kuser_memory_barrier() {
     let f: extern "C" fn() = mem::transmute(0xffff0fa0u32);
     f();
 }
+unsafe fn __kuser_helper_version() -> u32 {
+    ptr::read_volatile(0xffff0ffc as *const u32)
+}
+unsafe fn __kuser_cmpxchg64(oldval: u64, newval: u64, ptr: *mut u64) -> bool {
+    // This helper was only added in Linux 3.1 (helper version 5), and calling it on an older
+    // kernel would jump into unmapped memory, so fail loudly instead.
+    if __kuser_helper_version() < 5 {
+        intrinsics::abort();
+    }
+    let f: extern "C" fn(*const u64, *const u64, *mut u64) -> u32 = mem::transmute(0xffff0f60u32);
+    f(&oldval, &newval, ptr) == 0
+}
 
 // Word-align a pointer
 fn align_ptr<T>(ptr: *mut T) -> *mut u32 {
@@ -88,6 +101,36 @@ unsafe fn atomic_cmpxchg<T>(ptr: *mut T, oldval: u32, newval: u32) -> u32 {
     }
 }
 
+// Generic 64-bit atomic read-modify-write operation. The plain load may tear, but then the
+// compare-exchange fails and we simply retry.
+unsafe fn atomic_rmw_64<F: Fn(u64) -> u64>(ptr: *mut u64, f: F) -> u64 {
+    loop {
+        let curval = ptr::read_volatile(ptr);
+        let newval = f(curval);
+        if __kuser_cmpxchg64(curval, newval, ptr) {
+            return curval;
+        }
+    }
+}
+
+// Generic 64-bit atomic compare-exchange operation
+unsafe fn atomic_cmpxchg_64(ptr: *mut u64, oldval: u64, newval: u64) -> u64 {
+    loop {
+        let curval = ptr::read_volatile(ptr);
+        if curval != oldval {
+            // Make sure the mismatching value wasn't the result of a torn read by swapping it
+            // with itself before returning it.
+            if __kuser_cmpxchg64(curval, curval, ptr) {
+                return curval;
+            }
+            continue;
+        }
+        if __kuser_cmpxchg64(oldval, newval, ptr) {
+            return oldval;
+        }
+    }
+}
+
 macro_rules! atomic_rmw {
     ($name:ident, $ty:ty, $op:expr) => {
         #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
@@ -105,33 +148,58 @@ macro_rules! atomic_cmpxchg {
     };
 }
 
+macro_rules! atomic_rmw_64 {
+    ($name:ident, $ty:ty, $op:expr) => {
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name(ptr: *mut $ty, val: $ty) -> $ty {
+            atomic_rmw_64(ptr as *mut u64, |x| $op(x as $ty, val) as u64) as $ty
+        }
+    };
+}
+macro_rules! atomic_cmpxchg_64 {
+    ($name:ident, $ty:ty) => {
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name(ptr: *mut $ty, oldval: $ty, newval: $ty) -> $ty {
+            atomic_cmpxchg_64(ptr as *mut u64, oldval as u64, newval as u64) as $ty
+        }
+    };
+}
+
 atomic_rmw!(__sync_fetch_and_add_1, u8, |a: u8, b: u8| a.wrapping_add(b));
 atomic_rmw!(__sync_fetch_and_add_2, u16, |a: u16, b: u16| a
     .wrapping_add(b));
 atomic_rmw!(__sync_fetch_and_add_4, u32, |a: u32, b: u32| a
     .wrapping_add(b));
+atomic_rmw_64!(__sync_fetch_and_add_8, u64, |a: u64, b: u64| a
+    .wrapping_add(b));
 
 atomic_rmw!(__sync_fetch_and_sub_1, u8, |a: u8, b: u8| a.wrapping_sub(b));
 atomic_rmw!(__sync_fetch_and_sub_2, u16, |a: u16, b: u16| a
     .wrapping_sub(b));
 atomic_rmw!(__sync_fetch_and_sub_4, u32, |a: u32, b: u32| a
     .wrapping_sub(b));
+atomic_rmw_64!(__sync_fetch_and_sub_8, u64, |a: u64, b: u64| a
+    .wrapping_sub(b));
 
 atomic_rmw!(__sync_fetch_and_and_1, u8, |a: u8, b: u8| a & b);
 atomic_rmw!(__sync_fetch_and_and_2, u16, |a: u16, b: u16| a & b);
 atomic_rmw!(__sync_fetch_and_and_4, u32, |a: u32, b: u32| a & b);
+atomic_rmw_64!(__sync_fetch_and_and_8, u64, |a: u64, b: u64| a & b);
 
 atomic_rmw!(__sync_fetch_and_or_1, u8, |a: u8, b: u8| a | b);
 atomic_rmw!(__sync_fetch_and_or_2, u16, |a: u16, b: u16| a | b);
 atomic_rmw!(__sync_fetch_and_or_4, u32, |a: u32, b: u32| a | b);
+atomic_rmw_64!(__sync_fetch_and_or_8, u64, |a: u64, b: u64| a | b);
 
 atomic_rmw!(__sync_fetch_and_xor_1, u8, |a: u8, b: u8| a ^ b);
 atomic_rmw!(__sync_fetch_and_xor_2, u16, |a: u16, b: u16| a ^ b);
 atomic_rmw!(__sync_fetch_and_xor_4, u32, |a: u32, b: u32| a ^ b);
+atomic_rmw_64!(__sync_fetch_and_xor_8, u64, |a: u64, b: u64| a ^ b);
 
 atomic_rmw!(__sync_fetch_and_nand_1, u8, |a: u8, b: u8| !(a & b));
 atomic_rmw!(__sync_fetch_and_nand_2, u16, |a: u16, b: u16| !(a & b));
 atomic_rmw!(__sync_fetch_and_nand_4, u32, |a: u32, b: u32| !(a & b));
+atomic_rmw_64!(__sync_fetch_and_nand_8, u64, |a: u64, b: u64| !(a & b));
 
 atomic_rmw!(__sync_fetch_and_max_1, i8, |a: i8, b: i8| if a > b {
     a
@@ -148,6 +216,11 @@ atomic_rmw!(__sync_fetch_and_max_4, i32, |a: i32, b: i32| if a > b {
 } else {
     b
 });
+atomic_rmw_64!(__sync_fetch_and_max_8, i64, |a: i64, b: i64| if a > b {
+    a
+} else {
+    b
+});
 
 atomic_rmw!(__sync_fetch_and_umax_1, u8, |a: u8, b: u8| if a > b {
     a
@@ -164,6 +237,11 @@ atomic_rmw!(__sync_fetch_and_umax_4, u32, |a: u32, b: u32| if a > b {
 } else {
     b
 });
+atomic_rmw_64!(__sync_fetch_and_umax_8, u64, |a: u64, b: u64| if a > b {
+    a
+} else {
+    b
+});
 
 atomic_rmw!(__sync_fetch_and_min_1, i8, |a: i8, b: i8| if a < b {
     a
@@ -180,6 +258,11 @@ atomic_rmw!(__sync_fetch_and_min_4, i32, |a: i32, b: i32| if a < b {
 } else {
     b
 });
+atomic_rmw_64!(__sync_fetch_and_min_8, i64, |a: i64, b: i64| if a < b {
+    a
+} else {
+    b
+});
 
 atomic_rmw!(__sync_fetch_and_umin_1, u8, |a: u8, b: u8| if a < b {
     a
@@ -196,14 +279,21 @@ atomic_rmw!(__sync_fetch_and_umin_4, u32, |a: u32, b: u32| if a < b {
 } else {
     b
 });
+atomic_rmw_64!(__sync_fetch_and_umin_8, u64, |a: u64, b: u64| if a < b {
+    a
+} else {
+    b
+});
 
 atomic_rmw!(__sync_lock_test_and_set_1, u8, |_: u8, b: u8| b);
 atomic_rmw!(__sync_lock_test_and_set_2, u16, |_: u16, b: u16| b);
 atomic_rmw!(__sync_lock_test_and_set_4, u32, |_: u32, b: u32| b);
+atomic_rmw_64!(__sync_lock_test_and_set_8, u64, |_: u64, b: u64| b);
 
 atomic_cmpxchg!(__sync_val_compare_and_swap_1, u8);
 atomic_cmpxchg!(__sync_val_compare_and_swap_2, u16);
 atomic_cmpxchg!(__sync_val_compare_and_swap_4, u32);
+atomic_cmpxchg_64!(__sync_val_compare_and_swap_8, u64);
 
 #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
 pub unsafe extern "C" fn __sync_synchronize() {
@@ -0,0 +1,597 @@
+//! Lock-based implementations of the `__atomic_*` libcalls
+//!
+//! LLVM and GCC call these for the atomic operations that a target has no instructions for, like
+//! every read-modify-write operation on thumbv6m, riscv32i and msp430, or atomics wider than the
+//! target supports. Each operation holds a lock for the object it accesses, so an object must
+//! only be accessed through these functions.
+//!
+//! The sizes that the target has atomic instructions for are not locked. Like in compiler-rt, the
+//! generic functions use those instructions too for objects of such a size that are aligned to
+//! it, so that they never race with the sized functions.
+//!
+//! On targets with a pointer-sized compare-and-swap, the locks are a table of spinlocks indexed
+//! by a hash of the address. Other targets are assumed to have a single core, and use a critical
+//! section entered with `__rust_critical_section_acquire` and left with
+//! `__rust_critical_section_release` instead. The `critical-section` feature selects the critical
+//! section on every target. The defaults for M-profile ARM, MSP430 and RISC-V in machine mode
+//! mask interrupts. They have weak linkage, so that other environments can provide their own.
+//!
+//! This module is only built with the `atomic` feature.
+
+// The safety requirements are those of the C functions
+#![allow(clippy::missing_safety_doc)]
+
+#[cfg(target_has_atomic = "128")]
+use core::sync::atomic::AtomicU128;
+#[cfg(target_has_atomic = "16")]
+use core::sync::atomic::AtomicU16;
+#[cfg(target_has_atomic = "32")]
+use core::sync::atomic::AtomicU32;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+#[cfg(target_has_atomic = "8")]
+use core::sync::atomic::AtomicU8;
+#[allow(unused_imports)]
+use core::sync::atomic::Ordering::SeqCst;
+
+use mem::{memcmp, memcpy};
+
+#[allow(warnings)]
+#[cfg(target_pointer_width = "16")]
+type c_int = i16;
+#[allow(warnings)]
+#[cfg(not(target_pointer_width = "16"))]
+type c_int = i32;
+
+#[cfg(all(target_has_atomic = "ptr", not(feature = "critical-section")))]
+mod lock {
+    use core::hint::spin_loop;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The number of spinlocks, which must be a power of two
+    const COUNT: usize = 64;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const UNLOCKED: AtomicUsize = AtomicUsize::new(0);
+
+    static LOCKS: [AtomicUsize; COUNT] = [UNLOCKED; COUNT];
+
+    pub(super) type Guard = &'static AtomicUsize;
+
+    pub(super) fn acquire(ptr: *const u8) -> Guard {
+        // The objects are up to 16 bytes large and aligned to their size, so the lowest address
+        // bits say little about which object it is.
+        let addr = ptr as usize;
+        let lock = &LOCKS[((addr >> 4) ^ (addr >> 10)) & (COUNT - 1)];
+        while lock.swap(1, Ordering::Acquire) != 0 {
+            while lock.load(Ordering::Relaxed) != 0 {
+                spin_loop();
+            }
+        }
+        lock
+    }
+
+    pub(super) fn release(lock: Guard) {
+        lock.store(0, Ordering::Release);
+    }
+}
+
+#[cfg(any(not(target_has_atomic = "ptr"), feature = "critical-section"))]
+mod lock {
+    extern "C" {
+        fn __rust_critical_section_acquire() -> usize;
+        fn __rust_critical_section_release(state: usize);
+    }
+
+    /// The state to restore when leaving the critical section
+    pub(super) type Guard = usize;
+
+    pub(super) fn acquire(_ptr: *const u8) -> Guard {
+        unsafe { __rust_critical_section_acquire() }
+    }
+
+    pub(super) fn release(state: Guard) {
+        unsafe { __rust_critical_section_release(state) }
+    }
+}
+
+/// Runs `f` while holding the lock of the object at `ptr`
+fn locked<T>(ptr: *const u8, f: impl FnOnce() -> T) -> T {
+    let guard = lock::acquire(ptr);
+    let res = f();
+    lock::release(guard);
+    res
+}
+
+/// Returns whether the object of `size` bytes at `ptr` is accessed with atomic instructions
+fn is_lock_free(size: usize, ptr: *const u8) -> bool {
+    let native = match size {
+        1 => cfg!(target_has_atomic = "8"),
+        2 => cfg!(target_has_atomic = "16"),
+        4 => cfg!(target_has_atomic = "32"),
+        8 => cfg!(target_has_atomic = "64"),
+        16 => cfg!(target_has_atomic = "128"),
+        _ => false,
+    };
+    native && ptr as usize & (size - 1) == 0
+}
+
+/// Returns from the generic function with `$action!($ty, $f)` if the object of `$size` bytes at
+/// `$ptr` is lock-free, where `$f` is the sized function for the unsigned integer `$ty`
+macro_rules! lock_free_cases {
+    ($size:expr, $ptr:expr, $action:ident, $f1:ident, $f2:ident, $f4:ident, $f8:ident, $f16:ident) => {
+        if is_lock_free($size, $ptr as *const u8) {
+            match $size {
+                1 => return $action!(u8, $f1),
+                2 => return $action!(u16, $f2),
+                4 => return $action!(u32, $f4),
+                8 => return $action!(u64, $f8),
+                _ => return $action!(u128, $f16),
+            }
+        }
+    };
+}
+
+/// Disables interrupts, and returns the previous value of `PRIMASK`
+#[cfg(all(
+    not(feature = "no-asm"),
+    any(not(target_has_atomic = "ptr"), feature = "critical-section"),
+    target_arch = "arm",
+    target_feature = "mclass"
+))]
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn __rust_critical_section_acquire() -> usize {
+    let primask: usize;
+    unsafe {
+        asm!("mrs {}, PRIMASK", "cpsid i", out(reg) primask, options(nostack));
+    }
+    primask
+}
+
+/// Enables interrupts again, unless they were disabled before the critical section
+#[cfg(all(
+    not(feature = "no-asm"),
+    any(not(target_has_atomic = "ptr"), feature = "critical-section"),
+    target_arch = "arm",
+    target_feature = "mclass"
+))]
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn __rust_critical_section_release(primask: usize) {
+    if primask & 1 == 0 {
+        unsafe {
+            asm!("cpsie i", options(nostack));
+        }
+    }
+}
+
+/// Disables interrupts, and returns the previous status register
+#[cfg(all(
+    not(feature = "no-asm"),
+    any(not(target_has_atomic = "ptr"), feature = "critical-section"),
+    target_arch = "msp430"
+))]
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn __rust_critical_section_acquire() -> usize {
+    let sr: usize;
+    unsafe {
+        // `dint` takes effect after the next instruction
+        asm!("mov r2, {}", "dint", "nop", out(reg) sr, options(nostack));
+    }
+    sr
+}
+
+/// Enables interrupts again, unless they were disabled before the critical section
+#[cfg(all(
+    not(feature = "no-asm"),
+    any(not(target_has_atomic = "ptr"), feature = "critical-section"),
+    target_arch = "msp430"
+))]
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn __rust_critical_section_release(sr: usize) {
+    // GIE is bit 3 of the status register
+    if sr & 8 != 0 {
+        unsafe {
+            asm!("nop", "eint", "nop", options(nostack));
+        }
+    }
+}
+
+/// Disables machine mode interrupts, and returns the previous value of `mstatus`
+#[cfg(all(
+    not(feature = "no-asm"),
+    any(not(target_has_atomic = "ptr"), feature = "critical-section"),
+    target_arch = "riscv32"
+))]
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn __rust_critical_section_acquire() -> usize {
+    let mstatus: usize;
+    unsafe {
+        asm!("csrrci {}, mstatus, 8", out(reg) mstatus, options(nostack));
+    }
+    mstatus
+}
+
+/// Enables machine mode interrupts again, unless they were disabled before the critical section
+#[cfg(all(
+    not(feature = "no-asm"),
+    any(not(target_has_atomic = "ptr"), feature = "critical-section"),
+    target_arch = "riscv32"
+))]
+#[no_mangle]
+#[linkage = "weak"]
+pub extern "C" fn __rust_critical_section_release(mstatus: usize) {
+    // MIE is bit 3 of `mstatus`
+    if mstatus & 8 != 0 {
+        unsafe {
+            asm!("csrsi mstatus, 8", options(nostack));
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+pub unsafe extern "C" fn __atomic_load(size: usize, src: *const u8, dest: *mut u8, model: c_int) {
+    macro_rules! action {
+        ($ty:ident, $f:ident) => {
+            (dest as *mut $ty).write_unaligned($f(src as *const $ty, model))
+        };
+    }
+    lock_free_cases!(
+        size,
+        src,
+        action,
+        __atomic_load_1,
+        __atomic_load_2,
+        __atomic_load_4,
+        __atomic_load_8,
+        __atomic_load_16
+    );
+    locked(src, || {
+        memcpy(dest, src, size);
+    })
+}
+
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+pub unsafe extern "C" fn __atomic_store(size: usize, dest: *mut u8, src: *const u8, model: c_int) {
+    macro_rules! action {
+        ($ty:ident, $f:ident) => {
+            $f(
+                dest as *mut $ty,
+                (src as *const $ty).read_unaligned(),
+                model,
+            )
+        };
+    }
+    lock_free_cases!(
+        size,
+        dest,
+        action,
+        __atomic_store_1,
+        __atomic_store_2,
+        __atomic_store_4,
+        __atomic_store_8,
+        __atomic_store_16
+    );
+    locked(dest, || {
+        memcpy(dest, src, size);
+    })
+}
+
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+pub unsafe extern "C" fn __atomic_exchange(
+    size: usize,
+    ptr: *mut u8,
+    val: *const u8,
+    old: *mut u8,
+    model: c_int,
+) {
+    macro_rules! action {
+        ($ty:ident, $f:ident) => {
+            (old as *mut $ty).write_unaligned($f(
+                ptr as *mut $ty,
+                (val as *const $ty).read_unaligned(),
+                model,
+            ))
+        };
+    }
+    lock_free_cases!(
+        size,
+        ptr,
+        action,
+        __atomic_exchange_1,
+        __atomic_exchange_2,
+        __atomic_exchange_4,
+        __atomic_exchange_8,
+        __atomic_exchange_16
+    );
+    locked(ptr, || {
+        memcpy(old, ptr, size);
+        memcpy(ptr, val, size);
+    })
+}
+
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+pub unsafe extern "C" fn __atomic_compare_exchange(
+    size: usize,
+    ptr: *mut u8,
+    expected: *mut u8,
+    desired: *const u8,
+    success: c_int,
+    failure: c_int,
+) -> bool {
+    macro_rules! action {
+        ($ty:ident, $f:ident) => {{
+            let mut current = (expected as *const $ty).read_unaligned();
+            let res = $f(
+                ptr as *mut $ty,
+                &mut current,
+                (desired as *const $ty).read_unaligned(),
+                success,
+                failure,
+            );
+            (expected as *mut $ty).write_unaligned(current);
+            res
+        }};
+    }
+    lock_free_cases!(
+        size,
+        ptr,
+        action,
+        __atomic_compare_exchange_1,
+        __atomic_compare_exchange_2,
+        __atomic_compare_exchange_4,
+        __atomic_compare_exchange_8,
+        __atomic_compare_exchange_16
+    );
+    locked(ptr, || {
+        if memcmp(ptr, expected, size) == 0 {
+            memcpy(ptr, desired, size);
+            true
+        } else {
+            memcpy(expected, ptr, size);
+            false
+        }
+    })
+}
+
+#[allow(unused_macros)]
+macro_rules! atomic_fetch_op {
+    ($ty:ident, $name:ident, |$a:ident, $b:ident| $op:expr) => {
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $name(ptr: *mut $ty, val: $ty, _model: c_int) -> $ty {
+            locked(ptr as *const u8, || {
+                let $a = ptr.read();
+                let $b = val;
+                ptr.write($op);
+                $a
+            })
+        }
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! atomic_locked {
+    (
+        $ty:ident,
+        $load:ident,
+        $store:ident,
+        $exchange:ident,
+        $compare_exchange:ident,
+        $fetch_add:ident,
+        $fetch_sub:ident,
+        $fetch_and:ident,
+        $fetch_or:ident,
+        $fetch_xor:ident,
+        $fetch_nand:ident
+    ) => {
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $load(src: *const $ty, _model: c_int) -> $ty {
+            locked(src as *const u8, || src.read())
+        }
+
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $store(dest: *mut $ty, val: $ty, _model: c_int) {
+            locked(dest as *const u8, || dest.write(val))
+        }
+
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $exchange(ptr: *mut $ty, val: $ty, _model: c_int) -> $ty {
+            locked(ptr as *const u8, || ptr.replace(val))
+        }
+
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $compare_exchange(
+            ptr: *mut $ty,
+            expected: *mut $ty,
+            desired: $ty,
+            _success: c_int,
+            _failure: c_int,
+        ) -> bool {
+            locked(ptr as *const u8, || {
+                let current = ptr.read();
+                if current == *expected {
+                    ptr.write(desired);
+                    true
+                } else {
+                    *expected = current;
+                    false
+                }
+            })
+        }
+
+        atomic_fetch_op!($ty, $fetch_add, |a, b| a.wrapping_add(b));
+        atomic_fetch_op!($ty, $fetch_sub, |a, b| a.wrapping_sub(b));
+        atomic_fetch_op!($ty, $fetch_and, |a, b| a & b);
+        atomic_fetch_op!($ty, $fetch_or, |a, b| a | b);
+        atomic_fetch_op!($ty, $fetch_xor, |a, b| a ^ b);
+        atomic_fetch_op!($ty, $fetch_nand, |a, b| !(a & b));
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! atomic_native_fetch_op {
+    ($ty:ident, $atomic:ident, $name:ident, $op:ident) => {
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $name(ptr: *mut $ty, val: $ty, _model: c_int) -> $ty {
+            (*(ptr as *const $atomic)).$op(val, SeqCst)
+        }
+    };
+}
+
+// Sequential consistency is at least as strong as every memory order of the callers.
+#[allow(unused_macros)]
+macro_rules! atomic_native {
+    (
+        $ty:ident,
+        $atomic:ident,
+        $load:ident,
+        $store:ident,
+        $exchange:ident,
+        $compare_exchange:ident,
+        $fetch_add:ident,
+        $fetch_sub:ident,
+        $fetch_and:ident,
+        $fetch_or:ident,
+        $fetch_xor:ident,
+        $fetch_nand:ident
+    ) => {
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $load(src: *const $ty, _model: c_int) -> $ty {
+            (*(src as *const $atomic)).load(SeqCst)
+        }
+
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $store(dest: *mut $ty, val: $ty, _model: c_int) {
+            (*(dest as *const $atomic)).store(val, SeqCst)
+        }
+
+        atomic_native_fetch_op!($ty, $atomic, $exchange, swap);
+
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        #[cfg_attr(not(all(target_os = "windows", target_env = "gnu")), linkage = "weak")]
+        pub unsafe extern "C" fn $compare_exchange(
+            ptr: *mut $ty,
+            expected: *mut $ty,
+            desired: $ty,
+            _success: c_int,
+            _failure: c_int,
+        ) -> bool {
+            match (*(ptr as *const $atomic)).compare_exchange(*expected, desired, SeqCst, SeqCst) {
+                Ok(_) => true,
+                Err(current) => {
+                    *expected = current;
+                    false
+                }
+            }
+        }
+
+        atomic_native_fetch_op!($ty, $atomic, $fetch_add, fetch_add);
+        atomic_native_fetch_op!($ty, $atomic, $fetch_sub, fetch_sub);
+        atomic_native_fetch_op!($ty, $atomic, $fetch_and, fetch_and);
+        atomic_native_fetch_op!($ty, $atomic, $fetch_or, fetch_or);
+        atomic_native_fetch_op!($ty, $atomic, $fetch_xor, fetch_xor);
+        atomic_native_fetch_op!($ty, $atomic, $fetch_nand, fetch_nand);
+    };
+}
+
+/// Uses the atomic instructions for `$ty` if the target has them, and the locks otherwise
+macro_rules! atomic_sized {
+    ($ty:ident, $atomic:ident, $bits:tt, $($name:ident),+) => {
+        #[cfg(target_has_atomic = $bits)]
+        atomic_native!($ty, $atomic, $($name),+);
+        #[cfg(not(target_has_atomic = $bits))]
+        atomic_locked!($ty, $($name),+);
+    };
+}
+
+atomic_sized!(
+    u8,
+    AtomicU8,
+    "8",
+    __atomic_load_1,
+    __atomic_store_1,
+    __atomic_exchange_1,
+    __atomic_compare_exchange_1,
+    __atomic_fetch_add_1,
+    __atomic_fetch_sub_1,
+    __atomic_fetch_and_1,
+    __atomic_fetch_or_1,
+    __atomic_fetch_xor_1,
+    __atomic_fetch_nand_1
+);
+atomic_sized!(
+    u16,
+    AtomicU16,
+    "16",
+    __atomic_load_2,
+    __atomic_store_2,
+    __atomic_exchange_2,
+    __atomic_compare_exchange_2,
+    __atomic_fetch_add_2,
+    __atomic_fetch_sub_2,
+    __atomic_fetch_and_2,
+    __atomic_fetch_or_2,
+    __atomic_fetch_xor_2,
+    __atomic_fetch_nand_2
+);
+atomic_sized!(
+    u32,
+    AtomicU32,
+    "32",
+    __atomic_load_4,
+    __atomic_store_4,
+    __atomic_exchange_4,
+    __atomic_compare_exchange_4,
+    __atomic_fetch_add_4,
+    __atomic_fetch_sub_4,
+    __atomic_fetch_and_4,
+    __atomic_fetch_or_4,
+    __atomic_fetch_xor_4,
+    __atomic_fetch_nand_4
+);
+atomic_sized!(
+    u64,
+    AtomicU64,
+    "64",
+    __atomic_load_8,
+    __atomic_store_8,
+    __atomic_exchange_8,
+    __atomic_compare_exchange_8,
+    __atomic_fetch_add_8,
+    __atomic_fetch_sub_8,
+    __atomic_fetch_and_8,
+    __atomic_fetch_or_8,
+    __atomic_fetch_xor_8,
+    __atomic_fetch_nand_8
+);
+atomic_sized!(
+    u128,
+    AtomicU128,
+    "128",
+    __atomic_load_16,
+    __atomic_store_16,
+    __atomic_exchange_16,
+    __atomic_compare_exchange_16,
+    __atomic_fetch_add_16,
+    __atomic_fetch_sub_16,
+    __atomic_fetch_and_16,
+    __atomic_fetch_or_16,
+    __atomic_fetch_xor_16,
+    __atomic_fetch_nand_16
+);
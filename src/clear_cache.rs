@@ -0,0 +1,123 @@
+// `__clear_cache` makes the instructions written to `start..end` visible to instruction fetches,
+// which is what JITs call after emitting machine code. Where the caches are coherent it does
+// nothing.
+
+// Cleans the data cache and invalidates the instruction cache to the point of unification, one
+// cache line at a time.
+#[cfg(all(
+    target_arch = "aarch64",
+    not(target_os = "windows"),
+    not(feature = "no-asm")
+))]
+fn clear_cache(start: usize, end: usize) {
+    let ctr_el0: u64;
+    unsafe {
+        asm!("mrs {}, ctr_el0", out(reg) ctr_el0, options(nomem, nostack, preserves_flags));
+    }
+
+    // Cleaning the data cache is not needed when CTR_EL0.IDC is set
+    if ctr_el0 & (1 << 28) == 0 {
+        let line_size = 4 << ((ctr_el0 >> 16) & 0xf) as usize;
+        let mut addr = start & !(line_size - 1);
+        while addr < end {
+            unsafe { asm!("dc cvau, {}", in(reg) addr, options(nostack, preserves_flags)) };
+            addr += line_size;
+        }
+    }
+    unsafe { asm!("dsb ish", options(nostack, preserves_flags)) };
+
+    // Invalidating the instruction cache is not needed when CTR_EL0.DIC is set
+    if ctr_el0 & (1 << 29) == 0 {
+        let line_size = 4 << (ctr_el0 & 0xf) as usize;
+        let mut addr = start & !(line_size - 1);
+        while addr < end {
+            unsafe { asm!("ic ivau, {}", in(reg) addr, options(nostack, preserves_flags)) };
+            addr += line_size;
+        }
+        unsafe { asm!("dsb ish", options(nostack, preserves_flags)) };
+    }
+    unsafe { asm!("isb sy", options(nostack, preserves_flags)) };
+}
+
+// Only the kernel can maintain the caches, through the `cacheflush` system call
+#[cfg(all(
+    target_arch = "arm",
+    any(target_os = "linux", target_os = "android"),
+    not(feature = "no-asm")
+))]
+fn clear_cache(start: usize, end: usize) {
+    const __ARM_NR_CACHEFLUSH: usize = 0x0f0002;
+
+    // r7 holds the system call number, but it may also be the frame pointer, which cannot be
+    // used as an operand. It is saved and restored around the call instead.
+    unsafe {
+        asm!(
+            "mov {tmp}, r7",
+            "mov r7, {nr}",
+            "svc #0",
+            "mov r7, {tmp}",
+            nr = in(reg) __ARM_NR_CACHEFLUSH,
+            tmp = out(reg) _,
+            inout("r0") start => _,
+            in("r1") end,
+            in("r2") 0,
+            options(nostack, preserves_flags)
+        );
+    }
+}
+
+// `fence.i` only synchronizes the instruction and data streams of the current hart, and a thread
+// can migrate to another hart afterwards. The `riscv_flush_icache` system call has the kernel make
+// all harts that run the process execute `fence.i`.
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_os = "linux",
+    not(feature = "no-asm")
+))]
+fn clear_cache(start: usize, end: usize) {
+    const __NR_RISCV_FLUSH_ICACHE: usize = 259;
+
+    unsafe {
+        asm!(
+            "ecall",
+            inout("a0") start => _,
+            in("a1") end,
+            in("a2") 0,
+            in("a7") __NR_RISCV_FLUSH_ICACHE,
+            options(nostack, preserves_flags)
+        );
+    }
+}
+
+// Without an operating system, the code runs on the current hart only
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    not(target_os = "linux"),
+    not(feature = "no-asm")
+))]
+fn clear_cache(_start: usize, _end: usize) {
+    unsafe { asm!("fence.i", options(nostack, preserves_flags)) };
+}
+
+// The instruction cache is coherent with the data cache
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn clear_cache(_start: usize, _end: usize) {}
+
+intrinsics! {
+    #[cfg(any(
+        target_arch = "x86",
+        target_arch = "x86_64",
+        all(
+            any(
+                all(target_arch = "aarch64", not(target_os = "windows")),
+                all(target_arch = "arm", any(target_os = "linux", target_os = "android")),
+                target_arch = "riscv32",
+                target_arch = "riscv64",
+            ),
+            not(feature = "no-asm"),
+        ),
+    ))]
+    pub extern "C" fn __clear_cache(start: *mut u8, end: *mut u8) -> () {
+        clear_cache(start as usize, end as usize)
+    }
+}
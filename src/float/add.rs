@@ -1,13 +1,15 @@
+use float::fenv::{self, FE_INVALID};
+use float::round::{overflow, Round};
 use float::Float;
 use int::{CastInto, Int};
 
-/// Returns `a + b`
-fn add<F: Float>(a: F, b: F) -> F
+/// Returns `a + b`, rounded in the direction `round`
+pub(crate) const fn add<F: ~const Float>(a: F, b: F, round: Round) -> F
 where
-    u32: CastInto<F::Int>,
-    F::Int: CastInto<u32>,
-    i32: CastInto<F::Int>,
-    F::Int: CastInto<i32>,
+    u32: ~const CastInto<F::Int>,
+    F::Int: ~const CastInto<u32>,
+    i32: ~const CastInto<F::Int>,
+    F::Int: ~const CastInto<i32>,
 {
     let one = F::Int::ONE;
     let zero = F::Int::ZERO;
@@ -32,6 +34,9 @@ where
 
     // Detect if a or b is zero, infinity, or NaN.
     if a_abs.wrapping_sub(one) >= inf_rep - one || b_abs.wrapping_sub(one) >= inf_rep - one {
+        fenv::raise_if_signaling::<F>(a_abs);
+        fenv::raise_if_signaling::<F>(b_abs);
+
         // NaN + anything = qNaN
         if a_abs > inf_rep {
             return F::from_repr(a_abs | quiet_bit);
@@ -44,6 +49,7 @@ where
         if a_abs == inf_rep {
             // +/-infinity + -/+infinity = qNaN
             if (a.repr() ^ b.repr()) == sign_bit {
+                fenv::raise(FE_INVALID);
                 return F::from_repr(qnan_rep);
             } else {
                 // +/-infinity + anything remaining = +/- infinity
@@ -60,6 +66,9 @@ where
         if a_abs == Int::ZERO {
             // but we need to get the sign right for zero + zero
             if b_abs == Int::ZERO {
+                if matches!(round, Round::Down) {
+                    return F::from_repr(a.repr() | b.repr());
+                }
                 return F::from_repr(a.repr() & b.repr());
             } else {
                 return b;
@@ -115,8 +124,7 @@ where
     let align = a_exponent.wrapping_sub(b_exponent).cast();
     if align != Int::ZERO {
         if align < bits {
-            let sticky =
-                F::Int::from_bool(b_significand << bits.wrapping_sub(align).cast() != Int::ZERO);
+            let sticky = F::Int::from_bool(b_significand & ((one << align.cast()) - one) != zero);
             b_significand = (b_significand >> align.cast()) | sticky;
         } else {
             b_significand = one; // sticky; b is known to be non-zero.
@@ -124,8 +132,11 @@ where
     }
     if subtraction {
         a_significand = a_significand.wrapping_sub(b_significand);
-        // If a == -b, return +zero.
+        // If a == -b, return +zero, or -zero when rounding down.
         if a_significand == Int::ZERO {
+            if matches!(round, Round::Down) {
+                return F::from_repr(sign_bit);
+            }
             return F::from_repr(Int::ZERO);
         }
 
@@ -150,18 +161,19 @@ where
         }
     }
 
-    // If we have overflowed the type, return +/- infinity:
+    // If we have overflowed the type, return +/- infinity (or the largest finite
+    // number, depending on the rounding direction):
     if a_exponent >= max_exponent as i32 {
-        return F::from_repr(inf_rep | result_sign);
+        return overflow(result_sign != zero, round);
     }
 
-    if a_exponent <= 0 {
+    let tiny = a_exponent <= 0;
+    if tiny {
         // Result is denormal before rounding; the exponent is zero and we
         // need to shift the significand.
-        let shift = (1 - a_exponent).cast();
-        let sticky =
-            F::Int::from_bool((a_significand << bits.wrapping_sub(shift).cast()) != Int::ZERO);
-        a_significand = a_significand >> shift.cast() | sticky;
+        let shift = (1 - a_exponent) as u32;
+        let sticky = F::Int::from_bool(a_significand & ((one << shift) - one) != zero);
+        a_significand = a_significand >> shift | sticky;
         a_exponent = 0;
     }
 
@@ -178,12 +190,15 @@ where
 
     // Final rounding.  The result may overflow to infinity, but that is the
     // correct result in that case.
-    if round_guard_sticky > 0x4 {
+    if round.round_up(
+        result_sign != zero,
+        result & one != zero,
+        round_guard_sticky & 0x4 != 0,
+        round_guard_sticky & 0x3 != 0,
+    ) {
         result += one;
     }
-    if round_guard_sticky == 0x4 {
-        result += result & one;
-    }
+    fenv::raise_rounded(round_guard_sticky != 0, tiny, result & abs_mask >= inf_rep);
 
     F::from_repr(result)
 }
@@ -192,13 +207,13 @@ intrinsics! {
     #[aapcs_on_arm]
     #[arm_aeabi_alias = __aeabi_fadd]
     pub extern "C" fn __addsf3(a: f32, b: f32) -> f32 {
-        add(a, b)
+        add(a, b, Round::Nearest)
     }
 
     #[aapcs_on_arm]
     #[arm_aeabi_alias = __aeabi_dadd]
     pub extern "C" fn __adddf3(a: f64, b: f64) -> f64 {
-        add(a, b)
+        add(a, b, Round::Nearest)
     }
 
     #[cfg(target_arch = "arm")]
@@ -211,3 +226,17 @@ intrinsics! {
         a + b
     }
 }
+
+// `long double` on AArch64, MIPS64 and RV64, and `__float128` on x86_64. `f128` only has the C
+// ABI of these types on the targets below.
+#[cfg(any(
+    all(target_arch = "x86_64", not(windows)),
+    target_arch = "aarch64",
+    target_arch = "mips64",
+    target_arch = "riscv64"
+))]
+intrinsics! {
+    pub extern "C" fn __addtf3(a: ::float::f128, b: ::float::f128) -> ::float::f128 {
+        add(a, b, Round::Nearest)
+    }
+}
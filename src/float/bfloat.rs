@@ -0,0 +1,110 @@
+//! bfloat16, the upper half of an IEEE-754 binary32. Rust has no primitive for it, so `bf16` is
+//! only a storage format here and its arithmetic is carried out in `f32`. The significand of `f32`
+//! is more than twice as wide as the one of `bf16` plus two bits, so rounding twice gives the
+//! same results as rounding once.
+
+use core::{cmp, fmt, ops};
+
+use float::extend::extend;
+use float::trunc::trunc;
+
+/// A bfloat16 float
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct bf16(u16);
+
+impl bf16 {
+    /// Raw transmutation from `u16`
+    pub const fn from_bits(bits: u16) -> bf16 {
+        bf16(bits)
+    }
+
+    /// Raw transmutation to `u16`
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Returns `true` if this value is NaN
+    pub const fn is_nan(self) -> bool {
+        self.0 & 0x7fff > 0x7f80
+    }
+
+    fn to_f32(self) -> f32 {
+        extend(self)
+    }
+
+    fn from_f32(a: f32) -> bf16 {
+        trunc(a)
+    }
+}
+
+impl fmt::Debug for bf16 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bf16({:#06x})", self.0)
+    }
+}
+
+impl PartialEq for bf16 {
+    fn eq(&self, other: &bf16) -> bool {
+        self.to_f32() == other.to_f32()
+    }
+}
+
+impl PartialOrd for bf16 {
+    fn partial_cmp(&self, other: &bf16) -> Option<cmp::Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
+    }
+}
+
+impl ops::Neg for bf16 {
+    type Output = bf16;
+
+    fn neg(self) -> bf16 {
+        bf16(self.0 ^ 0x8000)
+    }
+}
+
+impl ops::Add for bf16 {
+    type Output = bf16;
+
+    fn add(self, rhs: bf16) -> bf16 {
+        bf16::from_f32(self.to_f32() + rhs.to_f32())
+    }
+}
+
+impl ops::Sub for bf16 {
+    type Output = bf16;
+
+    fn sub(self, rhs: bf16) -> bf16 {
+        bf16::from_f32(self.to_f32() - rhs.to_f32())
+    }
+}
+
+impl ops::Mul for bf16 {
+    type Output = bf16;
+
+    fn mul(self, rhs: bf16) -> bf16 {
+        bf16::from_f32(self.to_f32() * rhs.to_f32())
+    }
+}
+
+impl ops::Div for bf16 {
+    type Output = bf16;
+
+    fn div(self, rhs: bf16) -> bf16 {
+        bf16::from_f32(self.to_f32() / rhs.to_f32())
+    }
+}
+
+impl ops::AddAssign for bf16 {
+    fn add_assign(&mut self, rhs: bf16) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::MulAssign for bf16 {
+    fn mul_assign(&mut self, rhs: bf16) {
+        *self = *self * rhs;
+    }
+}
@@ -4,7 +4,7 @@ use float::Float;
 use int::Int;
 
 #[derive(Clone, Copy)]
-enum Result {
+pub(crate) enum Result {
     Less,
     Equal,
     Greater,
@@ -12,7 +12,7 @@ enum Result {
 }
 
 impl Result {
-    fn to_le_abi(self) -> i32 {
+    pub(crate) fn to_le_abi(self) -> i32 {
         match self {
             Result::Less => -1,
             Result::Equal => 0,
@@ -21,7 +21,7 @@ impl Result {
         }
     }
 
-    fn to_ge_abi(self) -> i32 {
+    pub(crate) fn to_ge_abi(self) -> i32 {
         match self {
             Result::Less => -1,
             Result::Equal => 0,
@@ -29,9 +29,21 @@ impl Result {
             Result::Unordered => -1,
         }
     }
+
+    /// Returns 0, 1 or 2 for "less", "equal" and "greater or unordered", which `cmp r0, #1` turns
+    /// into the flags that `__aeabi_cfcmple` and friends return
+    #[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+    pub(crate) fn to_flags_abi(self) -> i32 {
+        match self {
+            Result::Less => 0,
+            Result::Equal => 1,
+            Result::Greater => 2,
+            Result::Unordered => 2,
+        }
+    }
 }
 
-fn cmp<F: Float>(a: F, b: F) -> Result {
+pub(crate) fn cmp<F: Float>(a: F, b: F) -> Result {
     let one = F::Int::ONE;
     let zero = F::Int::ZERO;
     let szero = F::SignedInt::ZERO;
@@ -82,7 +94,7 @@ fn cmp<F: Float>(a: F, b: F) -> Result {
     }
 }
 
-fn unord<F: Float>(a: F, b: F) -> bool {
+pub(crate) fn unord<F: Float>(a: F, b: F) -> bool {
     let one = F::Int::ONE;
 
     let sign_bit = F::SIGN_MASK as F::Int;
@@ -98,6 +110,27 @@ fn unord<F: Float>(a: F, b: F) -> bool {
     a_abs > inf_rep || b_abs > inf_rep
 }
 
+// The cores of the flag-returning comparisons in `src/arm.rs`
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+pub(crate) extern "aapcs" fn cfcmple(a: f32, b: f32) -> i32 {
+    cmp(a, b).to_flags_abi()
+}
+
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+pub(crate) extern "aapcs" fn cfrcmple(a: f32, b: f32) -> i32 {
+    cmp(b, a).to_flags_abi()
+}
+
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+pub(crate) extern "aapcs" fn cdcmple(a: f64, b: f64) -> i32 {
+    cmp(a, b).to_flags_abi()
+}
+
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+pub(crate) extern "aapcs" fn cdrcmple(a: f64, b: f64) -> i32 {
+    cmp(b, a).to_flags_abi()
+}
+
 intrinsics! {
     pub extern "C" fn __lesf2(a: f32, b: f32) -> i32 {
         cmp(a, b).to_le_abi()
@@ -200,6 +233,14 @@ intrinsics! {
         (__gtdf2(a, b) > 0) as i32
     }
 
+    pub extern "aapcs" fn __aeabi_cfcmpeq_check_nan(a: f32, b: f32) -> i32 {
+        unord(a, b) as i32
+    }
+
+    pub extern "aapcs" fn __aeabi_cdcmpeq_check_nan(a: f64, b: f64) -> i32 {
+        unord(a, b) as i32
+    }
+
     // On hard-float targets LLVM will use native instructions
     // for all VFP intrinsics below
 
@@ -250,4 +291,12 @@ intrinsics! {
     pub extern "C" fn __eqdf2vfp(a: f64, b: f64) -> i32 {
         (a == b) as i32
     }
+
+    pub extern "C" fn __unordsf2vfp(a: f32, b: f32) -> i32 {
+        (a.is_nan() || b.is_nan()) as i32
+    }
+
+    pub extern "C" fn __unorddf2vfp(a: f64, b: f64) -> i32 {
+        (a.is_nan() || b.is_nan()) as i32
+    }
 }
@@ -0,0 +1,212 @@
+//! Multiplication and division of complex numbers, following the rules of Annex G of the C99
+//! standard for infinities and NaNs.
+
+use float::Float;
+use int::{CastInto, Int};
+
+/// A complex number, with the same layout as `_Complex float` and `_Complex double`, and also
+/// the same C ABI on the targets that get the intrinsics below
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Complex<F> {
+    pub re: F,
+    pub im: F,
+}
+
+fn abs<F: Float>(x: F) -> F {
+    F::from_repr(x.repr() & !F::SIGN_MASK)
+}
+
+fn copysign<F: Float>(magnitude: F, sign: F) -> F {
+    F::from_repr((magnitude.repr() & !F::SIGN_MASK) | (sign.repr() & F::SIGN_MASK))
+}
+
+fn is_nan<F: Float>(x: F) -> bool {
+    x.repr() & !F::SIGN_MASK > F::EXPONENT_MASK
+}
+
+fn is_infinite<F: Float>(x: F) -> bool {
+    x.repr() & !F::SIGN_MASK == F::EXPONENT_MASK
+}
+
+fn is_finite<F: Float>(x: F) -> bool {
+    x.repr() & F::EXPONENT_MASK != F::EXPONENT_MASK
+}
+
+fn infinity<F: Float>() -> F {
+    F::from_repr(F::EXPONENT_MASK)
+}
+
+/// Replaces an infinity by 1 and anything else by 0, keeping the sign
+fn box_infinity<F: Float>(x: F) -> F {
+    copysign(if is_infinite(x) { F::ONE } else { F::ZERO }, x)
+}
+
+/// Replaces a NaN by 0, keeping the sign
+fn nan_to_zero<F: Float>(x: F) -> F {
+    if is_nan(x) {
+        copysign(F::ZERO, x)
+    } else {
+        x
+    }
+}
+
+/// The unbiased exponent of a finite, non-zero `x`
+fn ilogb<F: Float>(x: F) -> i32
+where
+    F::Int: CastInto<i32>,
+{
+    let abs = x.repr() & !F::SIGN_MASK;
+    let exp: i32 = (abs >> F::SIGNIFICAND_BITS).cast();
+    if exp == 0 {
+        // subnormal, the exponent depends on the position of the leading one
+        let lz = abs.leading_zeros() - (F::Int::BITS - F::SIGNIFICAND_BITS);
+        -(F::EXPONENT_BIAS as i32) - lz as i32
+    } else {
+        exp - F::EXPONENT_BIAS as i32
+    }
+}
+
+/// `x * 2^n`, rounded once
+fn scalbn<F: Float>(mut x: F, mut n: i32) -> F
+where
+    u32: CastInto<F::Int>,
+{
+    let max_exp = F::EXPONENT_BIAS as i32;
+    let min_exp = 1 - max_exp;
+    let pow2 = |n: i32| F::from_parts(false, ((n + max_exp) as u32).cast(), F::Int::ZERO);
+
+    if n > max_exp {
+        x *= pow2(max_exp);
+        n -= max_exp;
+        if n > max_exp {
+            x *= pow2(max_exp);
+            n -= max_exp;
+            if n > max_exp {
+                n = max_exp;
+            }
+        }
+    } else if n < min_exp {
+        // Scaling down in steps that keep the intermediate results normal avoids rounding twice.
+        let step = min_exp + F::SIGNIFICAND_BITS as i32 + 1;
+        x *= pow2(min_exp) * pow2(F::SIGNIFICAND_BITS as i32 + 1);
+        n -= step;
+        if n < min_exp {
+            x *= pow2(min_exp) * pow2(F::SIGNIFICAND_BITS as i32 + 1);
+            n -= step;
+            if n < min_exp {
+                n = min_exp;
+            }
+        }
+    }
+    x * pow2(n)
+}
+
+pub(crate) fn mulc3<F: Float>(mut a: F, mut b: F, mut c: F, mut d: F) -> Complex<F> {
+    let ac = a * c;
+    let bd = b * d;
+    let ad = a * d;
+    let bc = b * c;
+    let mut re = ac - bd;
+    let mut im = ad + bc;
+    if is_nan(re) && is_nan(im) {
+        let mut recalc = false;
+        if is_infinite(a) || is_infinite(b) {
+            a = box_infinity(a);
+            b = box_infinity(b);
+            c = nan_to_zero(c);
+            d = nan_to_zero(d);
+            recalc = true;
+        }
+        if is_infinite(c) || is_infinite(d) {
+            c = box_infinity(c);
+            d = box_infinity(d);
+            a = nan_to_zero(a);
+            b = nan_to_zero(b);
+            recalc = true;
+        }
+        if !recalc && (is_infinite(ac) || is_infinite(bd) || is_infinite(ad) || is_infinite(bc)) {
+            a = nan_to_zero(a);
+            b = nan_to_zero(b);
+            c = nan_to_zero(c);
+            d = nan_to_zero(d);
+            recalc = true;
+        }
+        if recalc {
+            re = infinity::<F>() * (a * c - b * d);
+            im = infinity::<F>() * (a * d + b * c);
+        }
+    }
+    Complex { re, im }
+}
+
+pub(crate) fn divc3<F: Float>(mut a: F, mut b: F, mut c: F, mut d: F) -> Complex<F>
+where
+    F::Int: CastInto<i32>,
+    u32: CastInto<F::Int>,
+{
+    // `fmax(fabs(c), fabs(d))`, which only returns NaN if both are NaN
+    let max = if is_nan(c) || (!is_nan(d) && abs(d) > abs(c)) {
+        abs(d)
+    } else {
+        abs(c)
+    };
+    // Scale the divisor so that `c * c + d * d` can neither overflow nor underflow.
+    let mut ilogbw = 0;
+    if is_finite(max) && max != F::ZERO {
+        ilogbw = ilogb(max);
+        c = scalbn(c, -ilogbw);
+        d = scalbn(d, -ilogbw);
+    }
+    let denom = c * c + d * d;
+    let mut re = scalbn((a * c + b * d) / denom, -ilogbw);
+    let mut im = scalbn((b * c - a * d) / denom, -ilogbw);
+    if is_nan(re) && is_nan(im) {
+        if denom == F::ZERO && (!is_nan(a) || !is_nan(b)) {
+            re = copysign(infinity(), c) * a;
+            im = copysign(infinity(), c) * b;
+        } else if (is_infinite(a) || is_infinite(b)) && is_finite(c) && is_finite(d) {
+            a = box_infinity(a);
+            b = box_infinity(b);
+            re = infinity::<F>() * (a * c + b * d);
+            im = infinity::<F>() * (b * c - a * d);
+        } else if is_infinite(max) && is_finite(a) && is_finite(b) {
+            c = box_infinity(c);
+            d = box_infinity(d);
+            re = F::ZERO * (a * c + b * d);
+            im = F::ZERO * (b * c - a * d);
+        }
+    }
+    Complex { re, im }
+}
+
+// The C ABI of `_Complex` matches the one of a `repr(C)` struct of two floats on these targets.
+// 32-bit x86, for one, returns `_Complex float` in registers but structs in memory.
+#[cfg(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "arm",
+    target_arch = "riscv32",
+    target_arch = "riscv64"
+))]
+intrinsics! {
+    #[aapcs_on_arm]
+    pub extern "C" fn __mulsc3(a: f32, b: f32, c: f32, d: f32) -> ::float::complex::Complex<f32> {
+        mulc3(a, b, c, d)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __muldc3(a: f64, b: f64, c: f64, d: f64) -> ::float::complex::Complex<f64> {
+        mulc3(a, b, c, d)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __divsc3(a: f32, b: f32, c: f32, d: f32) -> ::float::complex::Complex<f32> {
+        divc3(a, b, c, d)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __divdc3(a: f64, b: f64, c: f64, d: f64) -> ::float::complex::Complex<f64> {
+        divc3(a, b, c, d)
+    }
+}
@@ -1,12 +1,15 @@
+use float::fenv::{self, FE_INEXACT, FE_INVALID};
+use float::round::Round;
 use float::Float;
 use int::{CastInto, Int};
 
-fn int_to_float<I: Int, F: Float>(i: I) -> F
+/// Returns `i` converted to `F`, rounded in the direction `round`
+pub(crate) const fn int_to_float<I: ~const Int, F: ~const Float>(i: I, round: Round) -> F
 where
-    F::Int: CastInto<u32>,
-    F::Int: CastInto<I>,
-    I::UnsignedInt: CastInto<F::Int>,
-    u32: CastInto<F::Int>,
+    F::Int: ~const CastInto<u32>,
+    F::Int: ~const CastInto<I>,
+    I::UnsignedInt: ~const CastInto<F::Int>,
+    u32: ~const CastInto<F::Int>,
 {
     if i == I::ZERO {
         return F::ZERO;
@@ -54,18 +57,27 @@ where
                 )
         };
 
-        // R |= P
-        x |= Int::from_bool((x & four) != I::UnsignedInt::ZERO);
-        // round - this step may add a significant bit
-        x += Int::ONE;
+        let odd = (x & four) != I::UnsignedInt::ZERO;
+        let half = (x & two) != I::UnsignedInt::ZERO;
+        let sticky = (x & I::UnsignedInt::ONE) != I::UnsignedInt::ZERO;
         // dump Q and R
         x >>= 2;
+        // round - this step may add a significant bit
+        if round.round_up(sign, odd, half, sticky) {
+            x += Int::ONE;
+        }
 
         // a is now rounded to f_sd or f_sd+1 bits
-        if (x & (I::UnsignedInt::ONE << f_sd)) != Int::ZERO {
+        if (x >> F::SIGNIFICAND_BITS) > I::UnsignedInt::ONE {
             x >>= 1;
             exp += 1;
         }
+        // `u128::MAX` even overflows `f32` when rounded up
+        fenv::raise_rounded(
+            half || sticky,
+            false,
+            exp + F::EXPONENT_BIAS >= F::EXPONENT_MAX,
+        );
         x
     } else {
         x.wrapping_shl(f_sd - i_sd)
@@ -77,12 +89,12 @@ where
 intrinsics! {
     #[arm_aeabi_alias = __aeabi_i2f]
     pub extern "C" fn __floatsisf(i: i32) -> f32 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[arm_aeabi_alias = __aeabi_i2d]
     pub extern "C" fn __floatsidf(i: i32) -> f64 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[maybe_use_optimized_c_shim]
@@ -93,7 +105,7 @@ intrinsics! {
         if cfg!(target_arch = "x86_64") {
             i as f32
         } else {
-            int_to_float(i)
+            int_to_float(i, Round::Nearest)
         }
     }
 
@@ -105,69 +117,109 @@ intrinsics! {
         if cfg!(target_arch = "x86_64") {
             i as f64
         } else {
-            int_to_float(i)
+            int_to_float(i, Round::Nearest)
         }
     }
 
     #[unadjusted_on_win64]
     pub extern "C" fn __floattisf(i: i128) -> f32 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[unadjusted_on_win64]
     pub extern "C" fn __floattidf(i: i128) -> f64 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[arm_aeabi_alias = __aeabi_ui2f]
     pub extern "C" fn __floatunsisf(i: u32) -> f32 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[arm_aeabi_alias = __aeabi_ui2d]
     pub extern "C" fn __floatunsidf(i: u32) -> f64 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[maybe_use_optimized_c_shim]
     #[arm_aeabi_alias = __aeabi_ul2f]
     pub extern "C" fn __floatundisf(i: u64) -> f32 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[maybe_use_optimized_c_shim]
     #[arm_aeabi_alias = __aeabi_ul2d]
     pub extern "C" fn __floatundidf(i: u64) -> f64 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[unadjusted_on_win64]
     pub extern "C" fn __floatuntisf(i: u128) -> f32 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
     }
 
     #[unadjusted_on_win64]
     pub extern "C" fn __floatuntidf(i: u128) -> f64 {
-        int_to_float(i)
+        int_to_float(i, Round::Nearest)
+    }
+
+    // On hard-float targets LLVM will use native instructions
+    // for all VFP intrinsics below
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __floatsisfvfp(i: i32) -> f32 {
+        i as f32
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __floatsidfvfp(i: i32) -> f64 {
+        i as f64
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __floatunssisfvfp(i: u32) -> f32 {
+        i as f32
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __floatunssidfvfp(i: u32) -> f64 {
+        i as f64
     }
 }
 
-fn float_to_int<F: Float, I: Int>(f: F) -> I
+/// Returns `f` truncated to an integer, saturating if it is out of the range of `I` and mapping
+/// NaN to zero like an `as` cast
+pub(crate) const fn float_to_int<F: ~const Float, I: ~const Int>(f: F) -> I
 where
-    F::ExpInt: CastInto<u32>,
-    u32: CastInto<F::ExpInt>,
-    F::Int: CastInto<I>,
+    F::ExpInt: ~const CastInto<u32>,
+    u32: ~const CastInto<F::ExpInt>,
+    F::Int: ~const CastInto<I>,
 {
-    // converting NaNs is UB, so we don't consider them
-
     let sign = f.sign();
     let mut exp = f.exp();
+    let sig_bits: F::ExpInt = F::SIGNIFICAND_BITS.cast();
 
-    // if less than one or unsigned & negative
-    if (exp < F::EXPONENT_BIAS.cast()) || (!I::SIGNED && sign) {
+    // if NaN
+    if exp == F::EXPONENT_MAX.cast() && f.frac() != F::Int::ZERO {
+        fenv::raise(FE_INVALID);
+        return I::ZERO;
+    }
+    // if less than one
+    if exp < F::EXPONENT_BIAS.cast() {
+        if f.repr() & !F::SIGN_MASK != F::Int::ZERO {
+            fenv::raise(FE_INEXACT);
+        }
+        return I::ZERO;
+    }
+    // if unsigned & negative
+    if !I::SIGNED && sign {
+        fenv::raise(FE_INVALID);
         return I::ZERO;
     }
     exp -= F::EXPONENT_BIAS.cast();
+    // if the truncation drops any bits
+    let fractional = exp < sig_bits
+        && f.frac() & ((F::Int::ONE << (sig_bits - exp).cast()) - F::Int::ONE) != F::Int::ZERO;
 
     // If the value is too large for `I`, saturate.
     let bits: F::ExpInt = I::BITS.cast();
@@ -177,6 +229,19 @@ where
         bits
     };
     if max <= exp {
+        // Only `I::MIN` itself, and values that truncate to it, are in range.
+        let int_frac = if exp < sig_bits {
+            f.frac() >> (sig_bits - exp).cast()
+        } else {
+            f.frac()
+        };
+        if sign && max == exp && int_frac == F::Int::ZERO {
+            if fractional {
+                fenv::raise(FE_INEXACT);
+            }
+        } else {
+            fenv::raise(FE_INVALID);
+        }
         return if sign {
             // It happens that I::MIN is handled correctly
             I::MIN
@@ -187,8 +252,11 @@ where
 
     // `0 <= exp < max`
 
+    if fractional {
+        fenv::raise(FE_INEXACT);
+    }
+
     // If 0 <= exponent < F::SIGNIFICAND_BITS, right shift to get the result. Otherwise, shift left.
-    let sig_bits: F::ExpInt = F::SIGNIFICAND_BITS.cast();
     // The larger integer has to be casted into, or else the shift overflows
     let r: I = if F::Int::BITS < I::BITS {
         let tmp: I = if exp < sig_bits {
@@ -273,4 +341,84 @@ intrinsics! {
     pub extern "C" fn __fixunsdfti(f: f64) -> u128 {
         float_to_int(f)
     }
+
+    // On hard-float targets LLVM will use native instructions
+    // for all VFP intrinsics below
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __fixsfsivfp(f: f32) -> i32 {
+        f as i32
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __fixdfsivfp(f: f64) -> i32 {
+        f as i32
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __fixunssfsivfp(f: f32) -> u32 {
+        f as u32
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __fixunsdfsivfp(f: f64) -> u32 {
+        f as u32
+    }
+}
+
+// Conversions with the semantics of an `as` cast in Rust, for code generators that call them
+// directly instead of going through the C names above. The float is truncated toward zero, values
+// out of range saturate to the minimum or maximum of the integer, and NaN becomes zero.
+intrinsics! {
+    pub extern "C" fn __rust_f32_to_i32_sat(f: f32) -> i32 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f32_to_u32_sat(f: f32) -> u32 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f32_to_i64_sat(f: f32) -> i64 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f32_to_u64_sat(f: f32) -> u64 {
+        float_to_int(f)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __rust_f32_to_i128_sat(f: f32) -> i128 {
+        float_to_int(f)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __rust_f32_to_u128_sat(f: f32) -> u128 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f64_to_i32_sat(f: f64) -> i32 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f64_to_u32_sat(f: f64) -> u32 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f64_to_i64_sat(f: f64) -> i64 {
+        float_to_int(f)
+    }
+
+    pub extern "C" fn __rust_f64_to_u64_sat(f: f64) -> u64 {
+        float_to_int(f)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __rust_f64_to_i128_sat(f: f64) -> i128 {
+        float_to_int(f)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __rust_f64_to_u128_sat(f: f64) -> u128 {
+        float_to_int(f)
+    }
 }
@@ -0,0 +1,389 @@
+//! IBM double-double, the format of `long double` on PowerPC64 Linux. A value is the unevaluated
+//! sum of two `f64`s, where the low part is at most half an ulp of the high part. The routines are
+//! ports of the `ppc` directory of compiler-rt and only need the `f64` arithmetic of the target.
+
+// The routines are only called by the intrinsics of PowerPC64, and by the tests elsewhere.
+#![cfg_attr(not(target_arch = "powerpc64"), allow(dead_code))]
+
+/// An IBM double-double, with the same layout as `long double` on PowerPC64
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+const INFINITY_HI: u64 = 0x7ff0_0000_0000_0000;
+
+/// Returns `true` if `x` is an infinity or a NaN
+fn is_non_finite(x: f64) -> bool {
+    x.to_bits() & INFINITY_HI == INFINITY_HI
+}
+
+fn abs(x: f64) -> f64 {
+    f64::from_bits(x.to_bits() & !(1 << 63))
+}
+
+/// Returns `x` with the low 27 bits of the significand cleared, so that its product with another
+/// such value is exact
+fn high26bits(x: f64) -> f64 {
+    f64::from_bits(x.to_bits() & 0xffff_ffff_f800_0000)
+}
+
+/// The rounding error of `xy`, the rounded product of `x = x_hi + x_lo` and `y = y_hi + y_lo`
+fn low_order(xy: f64, x_hi: f64, x_lo: f64, y_hi: f64, y_lo: f64) -> f64 {
+    (((x_hi * y_hi - xy) + x_hi * y_lo) + x_lo * y_hi) + x_lo * y_lo
+}
+
+public_test_dep! {
+/// Returns `x + y`
+pub(crate) fn add(x: DoubleDouble, y: DoubleDouble) -> DoubleDouble {
+    let (a_hi, a_lo, b_hi, b_lo) = (x.hi, x.lo, y.hi, y.lo);
+
+    // If both operands are zero, or either operand is NaN or infinity
+    if (a_hi == 0.0 && b_hi == 0.0) || is_non_finite(a_hi) || is_non_finite(b_hi) {
+        return DoubleDouble {
+            hi: a_hi + b_hi,
+            lo: 0.0,
+        };
+    }
+
+    // If the computation overflows. This may be playing things a little bit fast and loose, but
+    // it will do for a start.
+    let test_for_overflow = a_hi + (b_hi + (a_lo + b_lo));
+    if is_non_finite(test_for_overflow) {
+        return DoubleDouble {
+            hi: test_for_overflow,
+            lo: 0.0,
+        };
+    }
+
+    let big_h = b_hi + (a_hi - (a_hi + b_hi));
+    let big_t = b_lo + (a_lo - (a_lo + b_lo));
+    let h = a_hi + (b_hi - (a_hi + b_hi));
+    let t = a_lo + (b_lo - (a_lo + b_lo));
+
+    let mut w = if abs(a_hi) <= abs(b_hi) {
+        (a_lo + b_lo) + h
+    } else {
+        (a_lo + b_lo) + big_h
+    };
+    let big_w = (a_hi + b_hi) + w;
+    let mut y = (a_hi + b_hi) - big_w;
+    y += w;
+    w = if abs(a_lo) <= abs(b_lo) { t + y } else { big_t + y };
+
+    let hi = big_w + w;
+    DoubleDouble {
+        hi,
+        lo: (big_w - hi) + w,
+    }
+}
+}
+
+public_test_dep! {
+/// Returns `x * y`
+pub(crate) fn mul(x: DoubleDouble, y: DoubleDouble) -> DoubleDouble {
+    let (a_hi, a_lo, b_hi, b_lo) = (x.hi, x.lo, y.hi, y.lo);
+
+    // Detect special cases
+    let ab = a_hi * b_hi;
+    if ab == 0.0 || is_non_finite(ab) {
+        return DoubleDouble { hi: ab, lo: 0.0 };
+    }
+
+    // Generic cases handled here.
+    let a_hi_hi = high26bits(a_hi);
+    let b_hi_hi = high26bits(b_hi);
+    let a_hi_lo = a_hi - a_hi_hi;
+    let b_hi_lo = b_hi - b_hi_hi;
+
+    let mut tmp = low_order(ab, a_hi_hi, a_hi_lo, b_hi_hi, b_hi_lo);
+    tmp += a_hi * b_lo + a_lo * b_hi;
+    let tau = ab + tmp;
+    DoubleDouble {
+        hi: tau,
+        lo: (ab - tau) + tmp,
+    }
+}
+}
+
+public_test_dep! {
+/// Returns `x / y`
+pub(crate) fn div(x: DoubleDouble, y: DoubleDouble) -> DoubleDouble {
+    let (x_hi, x_lo, y_hi, y_lo) = (x.hi, x.lo, y.hi, y.lo);
+
+    // Detect special cases
+    let q = x_hi / y_hi;
+    if q == 0.0 || is_non_finite(q) {
+        return DoubleDouble { hi: q, lo: 0.0 };
+    }
+
+    let y_hi_hi = high26bits(y_hi);
+    let q_hi = high26bits(q);
+    let yq = y_hi * q;
+    let y_hi_lo = y_hi - y_hi_hi;
+    let q_lo = q - q_hi;
+
+    let mut tmp = low_order(yq, y_hi_hi, y_hi_lo, q_hi, q_lo);
+    tmp = (x_hi - yq) - tmp;
+    tmp += x_lo;
+    tmp -= q * y_lo;
+    tmp /= y_hi;
+    let hi = q + tmp;
+    DoubleDouble {
+        hi,
+        lo: (q - hi) + tmp,
+    }
+}
+}
+
+/// Returns the significand of `x` with its implicit bit, negated if the sign of `x` is set in
+/// `negation_mask`, shifted right by `shift` with the bits below rounded towards negative
+/// infinity
+fn tail_significand(x: f64, negation_mask: i64, shift: i32) -> i64 {
+    let bits = x.to_bits() as i64;
+    let significand = (bits & 0x000f_ffff_ffff_ffff) | 0x0010_0000_0000_0000;
+    let negation_mask = (bits >> 63) ^ negation_mask;
+    let significand = (significand ^ negation_mask) - negation_mask;
+    // Tails that are too small to reach the integer part still decide the rounding.
+    significand >> shift.min(63)
+}
+
+/// The biased exponent of `x`
+fn biased_exponent(x: f64) -> i32 {
+    (x.to_bits() >> 52) as i32 & 0x7ff
+}
+
+public_test_dep! {
+/// Returns `x` truncated to an integer, saturating if it is out of range
+pub(crate) fn to_i64(x: DoubleDouble) -> i64 {
+    let hi_bits = x.hi.to_bits();
+    let abs_high_word = (hi_bits >> 32) as u32 & 0x7fff_ffff;
+    let abs_high_word_minus_one = abs_high_word.wrapping_sub(0x3ff0_0000);
+
+    // If 1.0 <= |x.hi| < 2^63
+    if abs_high_word_minus_one < 0x03f0_0000 {
+        let unbiased_head_exponent = (abs_high_word_minus_one >> 20) as i32;
+        // The significand of the head, with one zero bit above it
+        let mut result = (hi_bits as i64 & 0x000f_ffff_ffff_ffff) | 0x0010_0000_0000_0000;
+        result <<= 10;
+        let hi_negation_mask = hi_bits as i64 >> 63;
+
+        // If the tail is non-zero, we need to patch in the tail bits, negated if head and tail
+        // have different signs and aligned to the head.
+        if x.lo != 0.0 {
+            let shift = unbiased_head_exponent - (biased_exponent(x.lo) - (1023 - 10));
+            result += tail_significand(x.lo, hi_negation_mask, shift);
+        }
+
+        result >>= 62 - unbiased_head_exponent;
+        // Restore the sign of the result
+        return (result ^ hi_negation_mask) - hi_negation_mask;
+    }
+
+    // |x| < 1, the result is zero.
+    if abs(x.hi) < 1.0 {
+        return 0;
+    }
+
+    // x is very close to `i64::MIN`, care must be taken to see which side we are on.
+    if x.hi == -9223372036854775808.0 {
+        // If the tail is positive, the result is something other than `i64::MIN`.
+        if x.lo > 0.0 {
+            return i64::MIN - tail_significand(x.lo, -1, 1075 - biased_exponent(x.lo));
+        }
+        return i64::MIN;
+    }
+
+    // Overflows, infinities and NaNs
+    if x.hi > 0.0 {
+        i64::MAX
+    } else {
+        i64::MIN
+    }
+}
+}
+
+public_test_dep! {
+/// Returns `x` truncated to an unsigned integer, saturating if it is out of range
+pub(crate) fn to_u64(x: DoubleDouble) -> u64 {
+    let hi_bits = x.hi.to_bits();
+    let high_word_minus_one = ((hi_bits >> 32) as u32).wrapping_sub(0x3ff0_0000);
+
+    // If 1.0 <= x.hi < 2^64
+    if high_word_minus_one < 0x0400_0000 {
+        let unbiased_head_exponent = (high_word_minus_one >> 20) as i32;
+        // The significand of the head, left aligned
+        let mut result = (hi_bits & 0x000f_ffff_ffff_ffff) | 0x0010_0000_0000_0000;
+        result <<= 11;
+
+        // If the tail is non-zero, we need to patch in the tail bits, aligned to the head.
+        if x.lo != 0.0 {
+            let shift = unbiased_head_exponent - (biased_exponent(x.lo) - (1023 - 11));
+            result = result.wrapping_add(tail_significand(x.lo, 0, shift) as u64);
+        }
+
+        return result >> (63 - unbiased_head_exponent);
+    }
+
+    // Negative values, overflows, infinities and NaNs saturate.
+    if x.hi < 1.0 {
+        0
+    } else {
+        u64::MAX
+    }
+}
+}
+
+/// Returns the exact sum of `high_addend` and `low` as a canonical double-double
+fn from_parts(high_addend: f64, low: f64) -> DoubleDouble {
+    let hi = high_addend + low;
+    DoubleDouble {
+        hi,
+        lo: (high_addend - hi) + low,
+    }
+}
+
+public_test_dep! {
+/// Returns `i` converted exactly
+pub(crate) fn from_i64(i: i64) -> DoubleDouble {
+    let two_p52 = f64::from_bits(0x4330 << 48);
+    // 2^52 plus the low 32 bits of `i`, and the high 32 bits of `i` minus that 2^52
+    let low = f64::from_bits(two_p52.to_bits() | (i as u64 & 0xffff_ffff));
+    let high_addend = ((i >> 32) as i32 as f64) * 4294967296.0 - two_p52;
+    from_parts(high_addend, low)
+}
+}
+
+public_test_dep! {
+/// Returns `i` converted exactly
+pub(crate) fn from_u64(i: u64) -> DoubleDouble {
+    let two_p52 = f64::from_bits(0x4330 << 48);
+    let two_p84 = f64::from_bits(0x4530 << 48);
+    // 2^84 plus the high 32 bits of `i`, and 2^52 plus the low 32 bits of `i`
+    let high = f64::from_bits(two_p84.to_bits() | (i >> 32));
+    let low = f64::from_bits(two_p52.to_bits() | (i & 0xffff_ffff));
+    let high_addend = high - (two_p84 + two_p52);
+    from_parts(high_addend, low)
+}
+}
+
+// `long double` arguments are passed like two `f64`s, and results are returned in `f1` and `f2`.
+#[cfg(target_arch = "powerpc64")]
+intrinsics! {
+    pub extern "C" fn __fixtfdi(a: f64, aa: f64) -> i64 {
+        to_i64(DoubleDouble { hi: a, lo: aa })
+    }
+
+    pub extern "C" fn __fixunstfdi(a: f64, aa: f64) -> u64 {
+        to_u64(DoubleDouble { hi: a, lo: aa })
+    }
+}
+
+// The ELFv2 ABI of little-endian PowerPC64 returns a struct of two `f64`s the same way.
+#[cfg(all(target_arch = "powerpc64", target_endian = "little"))]
+intrinsics! {
+    pub extern "C" fn __gcc_qadd(a: f64, aa: f64, c: f64, cc: f64) -> ::float::double_double::DoubleDouble {
+        add(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: c, lo: cc })
+    }
+
+    pub extern "C" fn __gcc_qsub(a: f64, aa: f64, c: f64, cc: f64) -> ::float::double_double::DoubleDouble {
+        add(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: -c, lo: -cc })
+    }
+
+    pub extern "C" fn __gcc_qmul(a: f64, aa: f64, c: f64, cc: f64) -> ::float::double_double::DoubleDouble {
+        mul(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: c, lo: cc })
+    }
+
+    pub extern "C" fn __gcc_qdiv(a: f64, aa: f64, c: f64, cc: f64) -> ::float::double_double::DoubleDouble {
+        div(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: c, lo: cc })
+    }
+
+    pub extern "C" fn __floatditf(i: i64) -> ::float::double_double::DoubleDouble {
+        from_i64(i)
+    }
+
+    pub extern "C" fn __floatunditf(i: u64) -> ::float::double_double::DoubleDouble {
+        from_u64(i)
+    }
+}
+
+/// Like `intrinsics!`, for the intrinsics that return a `long double` with the ELFv1 ABI of
+/// big-endian PowerPC64, which returns structs in memory. Each intrinsic is written as a Rust
+/// function, and the symbol is a naked function that calls it with space for the result on its
+/// stack and loads the result into `f1` and `f2`.
+///
+/// The pointer to the result is passed in `r3`, which moves an integer argument to `r4`. The
+/// `f64` arguments stay in the floating-point registers.
+#[allow(unused_macros)]
+macro_rules! elfv1_intrinsics {
+    ($(
+        pub extern "C" fn $name:ident($($arg:ident: $ty:ty),+) -> DoubleDouble {
+            $($body:tt)*
+        }
+    )*) => ($(
+        pub extern "C" fn $name($($arg: $ty),+) -> DoubleDouble {
+            $($body)*
+        }
+
+        pub mod $name {
+            extern "C" fn call(out: &mut super::DoubleDouble, $($arg: $ty),+) {
+                *out = super::$name($($arg),+);
+            }
+
+            #[naked]
+            #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+            pub unsafe extern "C" fn $name() {
+                asm!(
+                    "mflr 0",
+                    "std 0, 16(1)",
+                    "stdu 1, -128(1)",
+                    "mr 4, 3",
+                    "addi 3, 1, 112",
+                    "bl {f}",
+                    "nop",
+                    "lfd 1, 112(1)",
+                    "lfd 2, 120(1)",
+                    "addi 1, 1, 128",
+                    "ld 0, 16(1)",
+                    "mtlr 0",
+                    "blr",
+                    f = sym call,
+                    options(noreturn)
+                );
+            }
+        }
+    )*);
+}
+
+#[cfg(all(
+    target_arch = "powerpc64",
+    target_endian = "big",
+    not(feature = "no-asm")
+))]
+elfv1_intrinsics! {
+    pub extern "C" fn __gcc_qadd(a: f64, aa: f64, c: f64, cc: f64) -> DoubleDouble {
+        add(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: c, lo: cc })
+    }
+
+    pub extern "C" fn __gcc_qsub(a: f64, aa: f64, c: f64, cc: f64) -> DoubleDouble {
+        add(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: -c, lo: -cc })
+    }
+
+    pub extern "C" fn __gcc_qmul(a: f64, aa: f64, c: f64, cc: f64) -> DoubleDouble {
+        mul(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: c, lo: cc })
+    }
+
+    pub extern "C" fn __gcc_qdiv(a: f64, aa: f64, c: f64, cc: f64) -> DoubleDouble {
+        div(DoubleDouble { hi: a, lo: aa }, DoubleDouble { hi: c, lo: cc })
+    }
+
+    pub extern "C" fn __floatditf(i: i64) -> DoubleDouble {
+        from_i64(i)
+    }
+
+    pub extern "C" fn __floatunditf(i: u64) -> DoubleDouble {
+        from_u64(i)
+    }
+}
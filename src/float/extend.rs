@@ -1,16 +1,17 @@
+use float::fenv;
 use float::Float;
 use int::{CastInto, Int};
 
 /// Generic conversion from a narrower to a wider IEEE-754 floating-point type
-fn extend<F: Float, R: Float>(a: F) -> R
+pub(crate) const fn extend<F: ~const Float, R: ~const Float>(a: F) -> R
 where
-    F::Int: CastInto<u64>,
-    u64: CastInto<F::Int>,
-    u32: CastInto<R::Int>,
-    R::Int: CastInto<u32>,
-    R::Int: CastInto<u64>,
-    u64: CastInto<R::Int>,
-    F::Int: CastInto<R::Int>,
+    F::Int: ~const CastInto<u64>,
+    u64: ~const CastInto<F::Int>,
+    u32: ~const CastInto<R::Int>,
+    R::Int: ~const CastInto<u32>,
+    R::Int: ~const CastInto<u64>,
+    u64: ~const CastInto<R::Int>,
+    F::Int: ~const CastInto<R::Int>,
 {
     let src_zero = F::Int::ZERO;
     let src_one = F::Int::ONE;
@@ -48,12 +49,17 @@ where
         // Conjure the result by beginning with infinity, then setting the qNaN
         // bit (if needed) and right-aligning the rest of the trailing NaN
         // payload field.
+        fenv::raise_if_signaling::<F>(a_abs);
         let qnan_dst: R::Int = (a_abs & src_qnan).cast();
         let nan_code_dst: R::Int = (a_abs & src_nan_code).cast();
         let inf_exp_dst: R::Int = dst_inf_exp.cast();
         abs_result = inf_exp_dst.wrapping_shl(dst_sign_bits);
         abs_result |= qnan_dst.wrapping_shl(sign_bits_delta);
         abs_result |= nan_code_dst.wrapping_shl(sign_bits_delta);
+    } else if a_abs != src_zero && exp_bias_delta == 0 {
+        // a is denormal, and so is the result if the exponent ranges are the same.
+        let abs_dst: R::Int = a_abs.cast();
+        abs_result = abs_dst.wrapping_shl(sign_bits_delta);
     } else if a_abs != src_zero {
         // a is denormal.
         // Renormalize the significand and clear the leading bit, then insert
@@ -70,6 +76,22 @@ where
 }
 
 intrinsics! {
+    #[aapcs_on_arm]
+    pub extern "C" fn __extendbfsf2(a: ::float::bf16) -> f32 {
+        extend(a)
+    }
+
+    #[aapcs_on_arm]
+    #[arm_aeabi_alias = __aeabi_h2f]
+    pub extern "C" fn __extendhfsf2(a: ::float::f16) -> f32 {
+        extend(a)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __gnu_h2f_ieee(a: ::float::f16) -> f32 {
+        extend(a)
+    }
+
     #[aapcs_on_arm]
     #[arm_aeabi_alias = __aeabi_f2d]
     pub extern "C" fn  __extendsfdf2(a: f32) -> f64 {
@@ -0,0 +1,677 @@
+//! The 80-bit extended precision format of the x87 FPU, which is `long double` on x86 and x86_64
+//! outside of Windows. Unlike the IEEE-754 interchange formats, it stores the integer bit of the
+//! significand explicitly.
+//!
+//! The soft-float routines work on the representation without that bit, which is the one of a
+//! binary format with 63 significand bits and the 15-bit exponent of `f128`. It is also what the
+//! `Float` implementation of `f80` exposes, so the generic routines of this crate can be used too.
+
+// The naked functions below only have the signature of a C symbol, there is nothing to document.
+#![allow(clippy::missing_safety_doc)]
+
+use core::{cmp, fmt, ops};
+
+use float::round::Round;
+use float::{add, div, mul, Float};
+
+/// An x87 extended precision float, with the same layout as `long double` in memory
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct f80 {
+    significand: u64,
+    sign_exponent: u16,
+}
+
+impl f80 {
+    /// Raw transmutation from the 80 bits of an x87 register
+    pub const fn from_bits(bits: u128) -> f80 {
+        f80 {
+            significand: bits as u64,
+            sign_exponent: (bits >> 64) as u16,
+        }
+    }
+
+    /// Raw transmutation to the 80 bits of an x87 register
+    pub const fn to_bits(self) -> u128 {
+        (self.sign_exponent as u128) << 64 | self.significand as u128
+    }
+
+    /// Returns `true` if this value is NaN
+    pub const fn is_nan(self) -> bool {
+        self.pack() & !(1 << 78) > 0x7fff << 63
+    }
+
+    /// Returns the representation without the explicit integer bit
+    const fn pack(self) -> u128 {
+        let sign_exponent = (self.sign_exponent as u128) << 63;
+        if self.sign_exponent & 0x7fff == 0 {
+            // The integer bit of a pseudo-denormal carries into the exponent, which gives the
+            // same value.
+            sign_exponent | self.significand as u128
+        } else if self.significand >> 63 == 0 {
+            // Unnormals, pseudo-infinities and pseudo-NaNs are invalid operands on the x87.
+            // Turning them into signaling NaNs gets them the same treatment.
+            sign_exponent | 0x7fff << 63 | 1
+        } else {
+            sign_exponent | (self.significand & !(1 << 63)) as u128
+        }
+    }
+
+    /// Returns the float with the representation `repr`, without the explicit integer bit
+    const fn unpack(repr: u128) -> f80 {
+        let exponent = (repr >> 63) & 0x7fff;
+        f80 {
+            significand: repr as u64 & !(1 << 63) | ((exponent != 0) as u64) << 63,
+            sign_exponent: (repr >> 63) as u16,
+        }
+    }
+}
+
+impl const Float for f80 {
+    type Int = u128;
+    type SignedInt = i128;
+    type ExpInt = i32;
+
+    const ZERO: Self = f80::from_bits(0);
+    const ONE: Self = f80::from_bits(0x3fff << 64 | 1 << 63);
+
+    const BITS: u32 = 79;
+    const SIGNIFICAND_BITS: u32 = 63;
+
+    const SIGN_MASK: u128 = 1 << 78;
+    const SIGNIFICAND_MASK: u128 = (1 << 63) - 1;
+    const IMPLICIT_BIT: u128 = 1 << 63;
+    const EXPONENT_MASK: u128 = 0x7fff << 63;
+
+    fn repr(self) -> u128 {
+        self.pack()
+    }
+    fn signed_repr(self) -> i128 {
+        // Sign extend, so that it compares like the representation of a full width float
+        ((self.pack() << 49) as i128) >> 49
+    }
+    fn eq_repr(self, rhs: Self) -> bool {
+        if self.is_nan() && rhs.is_nan() {
+            true
+        } else {
+            self.repr() == rhs.repr()
+        }
+    }
+    fn sign(self) -> bool {
+        self.repr() & Self::SIGN_MASK != 0
+    }
+    fn exp(self) -> i32 {
+        ((self.repr() & Self::EXPONENT_MASK) >> Self::SIGNIFICAND_BITS) as i32
+    }
+    fn frac(self) -> u128 {
+        self.repr() & Self::SIGNIFICAND_MASK
+    }
+    fn imp_frac(self) -> u128 {
+        self.frac() | Self::IMPLICIT_BIT
+    }
+    fn from_repr(a: u128) -> Self {
+        f80::unpack(a)
+    }
+    fn from_parts(sign: bool, exponent: u128, significand: u128) -> Self {
+        Self::from_repr(
+            ((sign as u128) << (Self::BITS - 1))
+                | ((exponent << Self::SIGNIFICAND_BITS) & Self::EXPONENT_MASK)
+                | (significand & Self::SIGNIFICAND_MASK),
+        )
+    }
+    fn normalize(significand: u128) -> (i32, u128) {
+        let shift = significand
+            .leading_zeros()
+            .wrapping_sub(Self::IMPLICIT_BIT.leading_zeros());
+        (1i32.wrapping_sub(shift as i32), significand << shift)
+    }
+    fn is_subnormal(self) -> bool {
+        (self.repr() & Self::EXPONENT_MASK) == 0
+    }
+}
+
+impl fmt::Debug for f80 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "f80({:#022x})", self.to_bits())
+    }
+}
+
+impl PartialEq for f80 {
+    fn eq(&self, other: &f80) -> bool {
+        self.partial_cmp(other) == Some(cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for f80 {
+    fn partial_cmp(&self, other: &f80) -> Option<cmp::Ordering> {
+        match super::cmp::cmp(*self, *other) {
+            super::cmp::Result::Less => Some(cmp::Ordering::Less),
+            super::cmp::Result::Equal => Some(cmp::Ordering::Equal),
+            super::cmp::Result::Greater => Some(cmp::Ordering::Greater),
+            super::cmp::Result::Unordered => None,
+        }
+    }
+}
+
+impl ops::Neg for f80 {
+    type Output = f80;
+
+    fn neg(self) -> f80 {
+        f80 {
+            significand: self.significand,
+            sign_exponent: self.sign_exponent ^ 0x8000,
+        }
+    }
+}
+
+impl ops::Add for f80 {
+    type Output = f80;
+
+    fn add(self, rhs: f80) -> f80 {
+        add::add(self, rhs, Round::Nearest)
+    }
+}
+
+impl ops::Sub for f80 {
+    type Output = f80;
+
+    fn sub(self, rhs: f80) -> f80 {
+        add::add(self, -rhs, Round::Nearest)
+    }
+}
+
+impl ops::Mul for f80 {
+    type Output = f80;
+
+    fn mul(self, rhs: f80) -> f80 {
+        mul::mul(self, rhs, Round::Nearest)
+    }
+}
+
+impl ops::Div for f80 {
+    type Output = f80;
+
+    fn div(self, rhs: f80) -> f80 {
+        div::div(self, rhs, Round::Nearest)
+    }
+}
+
+impl ops::AddAssign for f80 {
+    fn add_assign(&mut self, rhs: f80) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::MulAssign for f80 {
+    fn mul_assign(&mut self, rhs: f80) {
+        *self = *self * rhs;
+    }
+}
+
+// The symbols of the intrinsics below are naked functions that translate between the C ABI of
+// `long double` and Rust functions. Each `$f` is the Rust function called by the shim.
+//
+// On both architectures `long double` arguments are passed on the stack, in slots of the size of
+// `f80`, and the result is returned in `st(0)`. On x86_64 the integer and `__float128` arguments
+// are passed in registers, while on x86 everything is on the stack and a struct is passed like a
+// `long double` of the same layout.
+#[allow(unused_macros)]
+macro_rules! x87_shim {
+    // `$f(out, args)` gets pointers to space for the result and to the arguments on the stack.
+    (out_and_args $name:ident, $f:path) => {
+        #[cfg(target_arch = "x86_64")]
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "lea 8(%rsp), %rsi",
+                "sub $24, %rsp",
+                "mov %rsp, %rdi",
+                "call {f}",
+                "fldt (%rsp)",
+                "add $24, %rsp",
+                "ret",
+                f = sym $f,
+                options(noreturn, att_syntax)
+            );
+        }
+
+        #[cfg(target_arch = "x86")]
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "lea 4(%esp), %ecx",
+                "sub $28, %esp",
+                "mov %ecx, 4(%esp)",
+                "lea 12(%esp), %eax",
+                "mov %eax, (%esp)",
+                "call {f}",
+                "fldt 12(%esp)",
+                "add $28, %esp",
+                "ret",
+                f = sym $f,
+                options(noreturn, att_syntax)
+            );
+        }
+    };
+    // x86_64 only: `$f(out, args, b)` also gets the `int` argument that follows the `long double`
+    (out_args_and_int $name:ident, $f:path) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "mov %edi, %edx",
+                "lea 8(%rsp), %rsi",
+                "sub $24, %rsp",
+                "mov %rsp, %rdi",
+                "call {f}",
+                "fldt (%rsp)",
+                "add $24, %rsp",
+                "ret",
+                f = sym $f,
+                options(noreturn, att_syntax)
+            );
+        }
+    };
+    // x86_64 only: `$f(out, args)` produces a complex result, which is returned in `st(0)` and
+    // `st(1)`.
+    (complex_out_and_args $name:ident, $f:path) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "lea 8(%rsp), %rsi",
+                "sub $40, %rsp",
+                "mov %rsp, %rdi",
+                "call {f}",
+                "fldt 16(%rsp)",
+                "fldt (%rsp)",
+                "add $40, %rsp",
+                "ret",
+                f = sym $f,
+                options(noreturn, att_syntax)
+            );
+        }
+    };
+    // x86_64 only: `$f(args)` gets a pointer to the arguments and returns in registers.
+    (args $name:ident, $f:path) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "lea 8(%rsp), %rdi",
+                "jmp {f}",
+                f = sym $f,
+                options(noreturn, att_syntax)
+            );
+        }
+    };
+    // x86_64 only: `$f` takes the arguments in registers and returns the `f80` in `rax:rdx`.
+    (registers $name:ident, $f:path) => {
+        #[naked]
+        #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+        pub unsafe extern "C" fn $name() {
+            asm!(
+                "sub $24, %rsp",
+                "call {f}",
+                "mov %rax, (%rsp)",
+                "mov %rdx, 8(%rsp)",
+                "fldt (%rsp)",
+                "add $24, %rsp",
+                "ret",
+                f = sym $f,
+                options(noreturn, att_syntax)
+            );
+        }
+    };
+}
+
+/// Like `intrinsics!`, for intrinsics that take or return `long double`. Rust functions cannot
+/// have its ABI, so each intrinsic is written as a Rust function on `f80`, and the symbol is a
+/// naked function made by `x87_shim!` that calls it.
+#[allow(unused_macros)]
+macro_rules! x87_intrinsics {
+    () => ();
+
+    // `_Complex long double` results
+    (
+        $(#[$attr:meta])*
+        pub extern "C" fn $name:ident($($arg:ident: f80),+) -> Complex<f80> {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$attr])*
+        pub extern "C" fn $name($($arg: f80),+) -> Complex<f80> {
+            $($body)*
+        }
+
+        $(#[$attr])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            #[repr(C)]
+            struct Args {
+                $($arg: ::float::extended::f80),+
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            extern "C" fn call(
+                out: &mut ::float::complex::Complex<::float::extended::f80>,
+                args: &Args,
+            ) {
+                *out = super::$name($(args.$arg),+);
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            x87_shim!(complex_out_and_args $name, call);
+
+            // A complex result is returned through a hidden pointer, like a struct.
+            #[cfg(target_arch = "x86")]
+            #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+            pub extern "C" fn $name($($arg: ::float::extended::f80),+)
+                -> ::float::complex::Complex<::float::extended::f80>
+            {
+                super::$name($($arg),+)
+            }
+        }
+
+        x87_intrinsics!($($rest)*);
+    );
+
+    // `long double` arguments and result
+    (
+        $(#[$attr:meta])*
+        pub extern "C" fn $name:ident($($arg:ident: f80),+) -> f80 {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$attr])*
+        pub extern "C" fn $name($($arg: f80),+) -> f80 {
+            $($body)*
+        }
+
+        $(#[$attr])*
+        pub mod $name {
+            #[repr(C)]
+            struct Args {
+                $($arg: ::float::extended::f80),+
+            }
+
+            extern "C" fn call(out: &mut ::float::extended::f80, args: &Args) {
+                *out = super::$name($(args.$arg),+);
+            }
+
+            x87_shim!(out_and_args $name, call);
+        }
+
+        x87_intrinsics!($($rest)*);
+    );
+
+    // `long double` arguments, with a result in registers
+    (
+        $(#[$attr:meta])*
+        pub extern "C" fn $name:ident($($arg:ident: f80),+) -> $ret:ty {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$attr])*
+        pub extern "C" fn $name($($arg: f80),+) -> $ret {
+            $($body)*
+        }
+
+        $(#[$attr])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            #[repr(C)]
+            struct Args {
+                $($arg: ::float::extended::f80),+
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            extern "C" fn call(args: &Args) -> $ret {
+                super::$name($(args.$arg),+)
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            x87_shim!(args $name, call);
+
+            #[cfg(target_arch = "x86")]
+            #[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+            pub extern "C" fn $name($($arg: ::float::extended::f80),+) -> $ret {
+                super::$name($($arg),+)
+            }
+        }
+
+        x87_intrinsics!($($rest)*);
+    );
+
+    // A `long double` and an `int` argument, with a `long double` result
+    (
+        $(#[$attr:meta])*
+        pub extern "C" fn $name:ident($a:ident: f80, $b:ident: i32) -> f80 {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$attr])*
+        pub extern "C" fn $name($a: f80, $b: i32) -> f80 {
+            $($body)*
+        }
+
+        $(#[$attr])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            extern "C" fn call(
+                out: &mut ::float::extended::f80,
+                $a: &::float::extended::f80,
+                $b: i32,
+            ) {
+                *out = super::$name(*$a, $b);
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            x87_shim!(out_args_and_int $name, call);
+
+            #[cfg(target_arch = "x86")]
+            #[repr(C)]
+            struct Args {
+                $a: ::float::extended::f80,
+                $b: i32,
+            }
+
+            #[cfg(target_arch = "x86")]
+            extern "C" fn call(out: &mut ::float::extended::f80, args: &Args) {
+                *out = super::$name(args.$a, args.$b);
+            }
+
+            #[cfg(target_arch = "x86")]
+            x87_shim!(out_and_args $name, call);
+        }
+
+        x87_intrinsics!($($rest)*);
+    );
+
+    // Other arguments, with a `long double` result
+    (
+        $(#[$attr:meta])*
+        pub extern "C" fn $name:ident($($arg:ident: $ty:ty),+) -> f80 {
+            $($body:tt)*
+        }
+
+        $($rest:tt)*
+    ) => (
+        $(#[$attr])*
+        pub extern "C" fn $name($($arg: $ty),+) -> f80 {
+            $($body)*
+        }
+
+        $(#[$attr])*
+        pub mod $name {
+            #[cfg(target_arch = "x86_64")]
+            x87_shim!(registers $name, super::$name);
+
+            #[cfg(target_arch = "x86")]
+            #[repr(C)]
+            struct Args {
+                $($arg: $ty),+
+            }
+
+            #[cfg(target_arch = "x86")]
+            extern "C" fn call(out: &mut ::float::extended::f80, args: &Args) {
+                *out = super::$name($(args.$arg),+);
+            }
+
+            #[cfg(target_arch = "x86")]
+            x87_shim!(out_and_args $name, call);
+        }
+
+        x87_intrinsics!($($rest)*);
+    );
+}
+
+#[cfg(all(
+    any(
+        target_arch = "x86_64",
+        all(target_arch = "x86", not(target_vendor = "apple"))
+    ),
+    not(any(windows, target_os = "uefi")),
+    not(feature = "no-asm")
+))]
+mod x87 {
+    use float::complex::{divc3, mulc3, Complex};
+    use float::conv::{float_to_int, int_to_float};
+    use float::extend::extend;
+    use float::round::Round;
+
+    use super::f80;
+
+    x87_intrinsics! {
+        pub extern "C" fn __floatdixf(i: i64) -> f80 {
+            int_to_float(i, Round::Nearest)
+        }
+
+        pub extern "C" fn __floatundixf(i: u64) -> f80 {
+            int_to_float(i, Round::Nearest)
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        pub extern "C" fn __floattixf(i: i128) -> f80 {
+            int_to_float(i, Round::Nearest)
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        pub extern "C" fn __floatuntixf(i: u128) -> f80 {
+            int_to_float(i, Round::Nearest)
+        }
+
+        pub extern "C" fn __fixxfdi(a: f80) -> i64 {
+            float_to_int(a)
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        pub extern "C" fn __fixxfti(a: f80) -> i128 {
+            float_to_int(a)
+        }
+
+        pub extern "C" fn __fixunsxfsi(a: f80) -> u32 {
+            float_to_int(a)
+        }
+
+        pub extern "C" fn __fixunsxfdi(a: f80) -> u64 {
+            float_to_int(a)
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        pub extern "C" fn __fixunsxfti(a: f80) -> u128 {
+            float_to_int(a)
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        pub extern "C" fn __extendxftf2(a: f80) -> ::float::f128 {
+            extend(a)
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        pub extern "C" fn __trunctfxf2(a: ::float::f128) -> f80 {
+            ::float::trunc::trunc(a)
+        }
+
+        pub extern "C" fn __addxf3(a: f80, b: f80) -> f80 {
+            a + b
+        }
+
+        pub extern "C" fn __subxf3(a: f80, b: f80) -> f80 {
+            a - b
+        }
+
+        pub extern "C" fn __mulxf3(a: f80, b: f80) -> f80 {
+            a * b
+        }
+
+        pub extern "C" fn __divxf3(a: f80, b: f80) -> f80 {
+            a / b
+        }
+
+        pub extern "C" fn __negxf2(a: f80) -> f80 {
+            -a
+        }
+
+        pub extern "C" fn __lexf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::cmp(a, b).to_le_abi()
+        }
+
+        pub extern "C" fn __gexf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::cmp(a, b).to_ge_abi()
+        }
+
+        pub extern "C" fn __unordxf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::unord(a, b) as i32
+        }
+
+        pub extern "C" fn __eqxf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::cmp(a, b).to_le_abi()
+        }
+
+        pub extern "C" fn __ltxf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::cmp(a, b).to_le_abi()
+        }
+
+        pub extern "C" fn __nexf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::cmp(a, b).to_le_abi()
+        }
+
+        pub extern "C" fn __gtxf2(a: f80, b: f80) -> i32 {
+            ::float::cmp::cmp(a, b).to_ge_abi()
+        }
+
+        pub extern "C" fn __powixf2(a: f80, b: i32) -> f80 {
+            ::float::pow::pow(a, b)
+        }
+
+        pub extern "C" fn __mulxc3(a: f80, b: f80, c: f80, d: f80) -> Complex<f80> {
+            mulc3(a, b, c, d)
+        }
+
+        pub extern "C" fn __divxc3(a: f80, b: f80, c: f80, d: f80) -> Complex<f80> {
+            divc3(a, b, c, d)
+        }
+    }
+}
+
+#[cfg(all(
+    any(
+        target_arch = "x86_64",
+        all(target_arch = "x86", not(target_vendor = "apple"))
+    ),
+    not(any(windows, target_os = "uefi")),
+    not(feature = "no-asm")
+))]
+pub use self::x87::*;
@@ -0,0 +1,299 @@
+//! Emulation of the IEEE-754 exception flags
+//!
+//! Hardware floating point units record the exceptions that an operation signals in sticky status
+//! flags, which C programs inspect with `fetestexcept`. With the `fenv` feature, the soft-float
+//! routines of this crate do the same: every operation that signals an exception raises its flag
+//! in a global set of flags, where it stays until it is cleared with `feclearexcept`. The flags
+//! are shared by all threads, like the status register of a single core. Without the feature,
+//! exceptions are not recorded and these functions are not available.
+//!
+//! Tininess is detected before rounding, so an underflow is signaled for a result that is inexact
+//! and below the normal range before it is rounded.
+
+use float::Float;
+use int::Int;
+
+/// An operation had no meaningful result, e.g. `0 / 0`, or an operand was a signaling NaN
+pub const FE_INVALID: u32 = 0x01;
+/// A finite non-zero number was divided by zero
+pub const FE_DIVBYZERO: u32 = 0x02;
+/// The rounded result was too large to be represented
+pub const FE_OVERFLOW: u32 = 0x04;
+/// The result was inexact and too small to be represented as a normal number
+pub const FE_UNDERFLOW: u32 = 0x08;
+/// The result had to be rounded
+pub const FE_INEXACT: u32 = 0x10;
+/// All of the exceptions
+pub const FE_ALL_EXCEPT: u32 = FE_INVALID | FE_DIVBYZERO | FE_OVERFLOW | FE_UNDERFLOW | FE_INEXACT;
+
+#[cfg(feature = "fenv")]
+static FLAGS: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+/// Raises the flags of `excepts`. There is no floating point environment during constant
+/// evaluation, so nothing is recorded there.
+#[inline]
+pub(crate) const fn raise(excepts: u32) {
+    #[cfg(feature = "fenv")]
+    core::intrinsics::const_eval_select((excepts,), raise_in_const, raise_at_runtime);
+    #[cfg(not(feature = "fenv"))]
+    let _ = excepts;
+}
+
+#[cfg(feature = "fenv")]
+const fn raise_in_const(_excepts: u32) {}
+
+#[cfg(feature = "fenv")]
+fn raise_at_runtime(excepts: u32) {
+    use core::sync::atomic::Ordering;
+
+    #[cfg(target_has_atomic = "ptr")]
+    FLAGS.fetch_or(excepts as usize, Ordering::Relaxed);
+    // Targets without read-modify-write atomics have a single core, where only an interrupt
+    // handler doing floating point could race with this.
+    #[cfg(not(target_has_atomic = "ptr"))]
+    FLAGS.store(
+        FLAGS.load(Ordering::Relaxed) | excepts as usize,
+        Ordering::Relaxed,
+    );
+}
+
+/// Raises the invalid flag if `abs`, a representation without the sign, is a signaling NaN
+#[inline]
+pub(crate) const fn raise_if_signaling<F: ~const Float>(abs: F::Int) {
+    if abs > F::EXPONENT_MASK && abs & (F::IMPLICIT_BIT >> 1) == F::Int::ZERO {
+        raise(FE_INVALID);
+    }
+}
+
+/// Raises the flags for a result that was rounded, where `inexact` tells if any bits were lost,
+/// `tiny` if the result was below the normal range before rounding and `infinite` if the rounded
+/// result is infinite
+#[inline]
+pub(crate) const fn raise_rounded(inexact: bool, tiny: bool, infinite: bool) {
+    if inexact {
+        let mut excepts = FE_INEXACT;
+        if tiny {
+            excepts |= FE_UNDERFLOW;
+        }
+        if infinite {
+            excepts |= FE_OVERFLOW;
+        }
+        raise(excepts);
+    }
+}
+
+/// Raises the flags of `excepts`, like `feraiseexcept` in C
+#[cfg(feature = "fenv")]
+pub fn feraiseexcept(excepts: u32) {
+    raise(excepts & FE_ALL_EXCEPT);
+}
+
+/// Clears the flags of `excepts`, like `feclearexcept` in C
+#[cfg(feature = "fenv")]
+pub fn feclearexcept(excepts: u32) {
+    use core::sync::atomic::Ordering;
+
+    let mask = !((excepts & FE_ALL_EXCEPT) as usize);
+    #[cfg(target_has_atomic = "ptr")]
+    FLAGS.fetch_and(mask, Ordering::Relaxed);
+    #[cfg(not(target_has_atomic = "ptr"))]
+    FLAGS.store(FLAGS.load(Ordering::Relaxed) & mask, Ordering::Relaxed);
+}
+
+/// Returns which of the flags of `excepts` are raised, like `fetestexcept` in C
+#[cfg(feature = "fenv")]
+pub fn fetestexcept(excepts: u32) -> u32 {
+    FLAGS.load(core::sync::atomic::Ordering::Relaxed) as u32 & excepts & FE_ALL_EXCEPT
+}
+
+// Where the floating point unit rounds and records exceptions, the C routines of compiler-rt
+// have to see its state, so the hooks below access it like the `fp_mode.c` of these targets.
+// `round_mode` returns a `CRT_FE_ROUND_MODE`: to nearest, downward, upward or toward zero.
+
+#[cfg(all(target_arch = "aarch64", not(feature = "no-asm")))]
+fn round_mode() -> i32 {
+    let fpcr: u64;
+    unsafe { asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack, preserves_flags)) };
+    // FPCR.RMode: to nearest, upward, downward, toward zero
+    match (fpcr >> 22) & 3 {
+        0 => 0,
+        1 => 2,
+        2 => 1,
+        _ => 3,
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", not(feature = "no-asm")))]
+fn raise_inexact() {
+    unsafe {
+        let fpsr: u64;
+        asm!("mrs {}, fpsr", out(reg) fpsr, options(nomem, nostack, preserves_flags));
+        // FPSR.IXC
+        asm!("msr fpsr, {}", in(reg) fpsr | 0x10, options(nomem, nostack, preserves_flags));
+    }
+}
+
+#[cfg(all(target_arch = "arm", target_feature = "vfp2", not(feature = "no-asm")))]
+fn round_mode() -> i32 {
+    let fpscr: u32;
+    unsafe { asm!("vmrs {}, fpscr", out(reg) fpscr, options(nomem, nostack, preserves_flags)) };
+    // FPSCR.RMode: to nearest, upward, downward, toward zero
+    match (fpscr >> 22) & 3 {
+        0 => 0,
+        1 => 2,
+        2 => 1,
+        _ => 3,
+    }
+}
+
+#[cfg(all(target_arch = "arm", target_feature = "vfp2", not(feature = "no-asm")))]
+fn raise_inexact() {
+    unsafe {
+        let fpscr: u32;
+        asm!("vmrs {}, fpscr", out(reg) fpscr, options(nomem, nostack, preserves_flags));
+        // FPSCR.IXC
+        asm!("vmsr fpscr, {}", in(reg) fpscr | 0x10, options(nomem, nostack, preserves_flags));
+    }
+}
+
+// All of the MIPS64 targets have a floating point unit
+#[cfg(all(target_arch = "mips64", not(feature = "no-asm")))]
+fn round_mode() -> i32 {
+    let fcsr: u32;
+    unsafe { asm!("cfc1 {}, $31", out(reg) fcsr, options(nomem, nostack, preserves_flags)) };
+    // FCSR.RM: to nearest, toward zero, upward, downward
+    match fcsr & 3 {
+        0 => 0,
+        1 => 3,
+        2 => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(all(target_arch = "mips64", not(feature = "no-asm")))]
+fn raise_inexact() {
+    unsafe {
+        let fcsr: u32;
+        asm!("cfc1 {}, $31", out(reg) fcsr, options(nomem, nostack, preserves_flags));
+        // The inexact bit of FCSR.Flags
+        asm!("ctc1 {}, $31", in(reg) fcsr | 0x4, options(nomem, nostack, preserves_flags));
+    }
+}
+
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_feature = "f",
+    not(feature = "no-asm")
+))]
+fn round_mode() -> i32 {
+    let frm: usize;
+    unsafe { asm!("frrm {}", out(reg) frm, options(nomem, nostack, preserves_flags)) };
+    // frm: to nearest (ties to even), toward zero, downward, upward, to nearest (ties away)
+    match frm {
+        1 => 3,
+        2 => 1,
+        3 => 2,
+        _ => 0,
+    }
+}
+
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    target_feature = "f",
+    not(feature = "no-asm")
+))]
+fn raise_inexact() {
+    // fflags.NX
+    unsafe { asm!("csrsi fflags, 1", options(nomem, nostack, preserves_flags)) };
+}
+
+// The rounding mode is read from the x87 control word, which `fesetround` keeps in sync with the
+// SSE one.
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(feature = "no-asm")
+))]
+fn round_mode() -> i32 {
+    let mut cw: u16 = 0;
+    unsafe {
+        asm!("fnstcw word ptr [{}]", in(reg) &mut cw, options(nostack, preserves_flags));
+    }
+    // The rounding control field has the same encoding as `CRT_FE_ROUND_MODE`
+    ((cw >> 10) & 3) as i32
+}
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(feature = "no-asm")
+))]
+fn raise_inexact() {
+    // 1 / 3 is inexact
+    unsafe {
+        asm!(
+            "fld1",
+            "fdiv dword ptr [{}]",
+            "fstp st(0)",
+            in(reg) &3.0f32,
+            out("st(0)") _,
+            out("st(1)") _,
+            out("st(2)") _,
+            out("st(3)") _,
+            out("st(4)") _,
+            out("st(5)") _,
+            out("st(6)") _,
+            out("st(7)") _,
+            options(nostack),
+        );
+    }
+}
+
+// Without a floating point unit, or without the assembly to access it, the rounding mode is the
+// one of the soft-float routines and exceptions only go to the emulated flags.
+#[cfg(not(all(
+    any(
+        target_arch = "aarch64",
+        all(target_arch = "arm", target_feature = "vfp2"),
+        target_arch = "mips64",
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_feature = "f"
+        ),
+        target_arch = "x86",
+        target_arch = "x86_64",
+    ),
+    not(feature = "no-asm")
+)))]
+fn round_mode() -> i32 {
+    // CRT_FE_TONEAREST
+    0
+}
+
+#[cfg(not(all(
+    any(
+        target_arch = "aarch64",
+        all(target_arch = "arm", target_feature = "vfp2"),
+        target_arch = "mips64",
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_feature = "f"
+        ),
+        target_arch = "x86",
+        target_arch = "x86_64",
+    ),
+    not(feature = "no-asm")
+)))]
+fn raise_inexact() {}
+
+intrinsics! {
+    // Hooks that compiler-rt's soft-float routines call to honor the floating point environment.
+    // The intrinsics of this crate always round to nearest, ties to even.
+    pub extern "C" fn __fe_getround() -> i32 {
+        round_mode()
+    }
+
+    pub extern "C" fn __fe_raise_inexact() -> i32 {
+        raise_inexact();
+        raise(FE_INEXACT);
+        0
+    }
+}
@@ -0,0 +1,233 @@
+use float::fenv::{self, FE_INEXACT, FE_INVALID, FE_OVERFLOW};
+use float::round::Round;
+use float::Float;
+use int::{CastInto, DInt, HInt, Int};
+
+public_test_dep! {
+/// Returns `a * b + c` with a single rounding to nearest, ties to even
+///
+/// The product of the significands is exact in an integer of twice the width of the float. The
+/// addend is aligned to it in the same integer, where there is enough room below both of them
+/// that the bits shifted out only matter through a sticky bit.
+pub(crate) fn soft_fma<F: Float>(a: F, b: F, c: F) -> F
+where
+    F::Int: HInt,
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+{
+    let one = F::Int::ONE;
+    let zero = F::Int::ZERO;
+    let wide_one = one.zero_widen();
+    let wide_zero = zero.zero_widen();
+
+    let bits = F::BITS;
+    let significand_bits = F::SIGNIFICAND_BITS;
+    let max_exponent = F::EXPONENT_MAX;
+
+    let exponent_bias = F::EXPONENT_BIAS;
+
+    let implicit_bit = F::IMPLICIT_BIT;
+    let significand_mask = F::SIGNIFICAND_MASK;
+    let sign_bit = F::SIGN_MASK as F::Int;
+    let abs_mask = sign_bit - one;
+    let exponent_mask = F::EXPONENT_MASK;
+    let inf_rep = exponent_mask;
+    let quiet_bit = implicit_bit >> 1;
+    let qnan_rep = exponent_mask | quiet_bit;
+
+    let a_rep = a.repr();
+    let b_rep = b.repr();
+    let c_rep = c.repr();
+    let a_abs = a_rep & abs_mask;
+    let b_abs = b_rep & abs_mask;
+    let c_abs = c_rep & abs_mask;
+    let product_sign = (a_rep ^ b_rep) & sign_bit;
+    let c_sign = c_rep & sign_bit;
+
+    // NaN * anything + anything = qNaN, and so on
+    if a_abs > inf_rep || b_abs > inf_rep || c_abs > inf_rep {
+        fenv::raise_if_signaling::<F>(a_abs);
+        fenv::raise_if_signaling::<F>(b_abs);
+        fenv::raise_if_signaling::<F>(c_abs);
+        let nan = if a_abs > inf_rep {
+            a_rep
+        } else if b_abs > inf_rep {
+            b_rep
+        } else {
+            c_rep
+        };
+        return F::from_repr(nan | quiet_bit);
+    }
+
+    if a_abs == inf_rep || b_abs == inf_rep {
+        if a_abs == zero || b_abs == zero {
+            // infinity * zero + anything = NaN
+            fenv::raise(FE_INVALID);
+            return F::from_repr(qnan_rep);
+        }
+        if c_abs == inf_rep && c_sign != product_sign {
+            // infinity - infinity = NaN
+            fenv::raise(FE_INVALID);
+            return F::from_repr(qnan_rep);
+        }
+        // infinity * non-zero + finite = +/- infinity
+        return F::from_repr(inf_rep | product_sign);
+    }
+
+    // finite * finite + infinity = +/- infinity
+    if c_abs == inf_rep {
+        return c;
+    }
+
+    if a_abs == zero || b_abs == zero {
+        // zero * finite + non-zero = the addend, and the sum of zeros of opposite signs is +0
+        return if c_abs == zero {
+            F::from_repr(product_sign & c_sign)
+        } else {
+            c
+        };
+    }
+
+    // Splits a finite non-zero float into its significand with the implicit bit set and its
+    // biased exponent, renormalizing denormals.
+    let split = |abs: F::Int| -> (i32, F::Int) {
+        if abs < implicit_bit {
+            F::normalize(abs)
+        } else {
+            (
+                CastInto::<u32>::cast(abs >> significand_bits) as i32,
+                (abs & significand_mask) | implicit_bit,
+            )
+        }
+    };
+    // The exponent of the last bit of a significand
+    let unscaled = (exponent_bias + significand_bits) as i32;
+
+    // Get the exact product and move it to the top of the wide integer, leaving two bits of
+    // headroom for the carry of the sum. `x` is the product scaled by `2^x_exponent`.
+    let wide_bits = bits * 2;
+    let (a_exponent, a_significand) = split(a_abs);
+    let (b_exponent, b_significand) = split(b_abs);
+    let product = a_significand.widen_mul(b_significand);
+    let shift = product.leading_zeros() - 2;
+    let mut x = product << shift;
+    let mut x_exponent = a_exponent + b_exponent - 2 * unscaled - shift as i32;
+    let mut x_sign = product_sign;
+
+    // Do the same with the addend. A zero addend does not need any alignment.
+    let (mut y, mut y_exponent) = if c_abs == zero {
+        (wide_zero, x_exponent)
+    } else {
+        let (c_exponent, c_significand) = split(c_abs);
+        let c_significand = c_significand.zero_widen();
+        let shift = c_significand.leading_zeros() - 2;
+        (c_significand << shift, c_exponent - unscaled - shift as i32)
+    };
+    let mut y_sign = c_sign;
+
+    // Make `x` the operand of larger magnitude, up to the alignment.
+    if y_exponent > x_exponent {
+        core::mem::swap(&mut x, &mut y);
+        core::mem::swap(&mut x_exponent, &mut y_exponent);
+        core::mem::swap(&mut x_sign, &mut y_sign);
+    }
+
+    // Align `y` to `x`, collecting the bits that are shifted out in a sticky bit. The lowest bits
+    // of both operands are zero, so a sticky bit can only appear if `x` is so much larger that
+    // the sum keeps the high bits of `x` and the sticky bit is far below its rounding position.
+    let align = (x_exponent - y_exponent) as u32;
+    if align >= wide_bits {
+        y = Int::from_bool(y != wide_zero);
+    } else if align != 0 {
+        y = (y >> align) | Int::from_bool(y << (wide_bits - align) != wide_zero);
+    }
+
+    let sum = if x_sign == y_sign {
+        x + y
+    } else if x >= y {
+        x - y
+    } else {
+        x_sign = y_sign;
+        y - x
+    };
+
+    // x - x = +0 when rounding to nearest
+    if sum == wide_zero {
+        return F::from_repr(zero);
+    }
+
+    // The biased exponent of the result before rounding
+    let msb = wide_bits - 1 - sum.leading_zeros();
+    let exponent = x_exponent + msb as i32 + exponent_bias as i32;
+
+    // If we have overflowed the type, return +/- infinity.
+    if exponent >= max_exponent as i32 {
+        fenv::raise(FE_OVERFLOW | FE_INEXACT);
+        return F::from_repr(inf_rep | x_sign);
+    }
+
+    // The number of bits of the sum below the last bit of the result, which has fewer
+    // significant bits if it is denormal. Small sums may be exact with room to spare.
+    let tiny = exponent < 1;
+    let shift = msb as i32 - significand_bits as i32 + if tiny { 1 - exponent } else { 0 };
+    let (significand, half, sticky) = if shift <= 0 {
+        ((sum << (-shift) as u32).lo(), false, false)
+    } else if shift as u32 >= wide_bits {
+        (zero, false, true)
+    } else {
+        let shift = shift as u32;
+        (
+            (sum >> shift).lo(),
+            (sum >> (shift - 1)) & wide_one != wide_zero,
+            sum & ((wide_one << (shift - 1)) - wide_one) != wide_zero,
+        )
+    };
+
+    // Insert the exponent; the implicit bit of a normal significand adds one to it. Rounding a
+    // denormal up may carry into the exponent, which gives the correct result.
+    let mut abs_result = if tiny {
+        significand
+    } else {
+        let exponent: F::Int = ((exponent - 1) as u32).cast();
+        (exponent << significand_bits) + significand
+    };
+    if Round::Nearest.round_up(x_sign != zero, abs_result & one != zero, half, sticky) {
+        abs_result += one;
+    }
+    fenv::raise_rounded(half || sticky, tiny, abs_result >= inf_rep);
+    F::from_repr(abs_result | x_sign)
+}
+}
+
+// LLVM lowers `f32::mul_add` and `f64::mul_add` to these on targets without instructions for them. Only
+// the targets without a libm get them here, where `math` provides the rest of it. Elsewhere they
+// come from the libm of the system, which they must not shadow.
+intrinsics! {
+    #[cfg(any(
+        all(target_arch = "arm", target_os = "none"),
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_os = "none",
+            not(target_feature = "f")
+        ),
+        all(target_arch = "wasm32", target_os = "unknown"),
+        all(target_vendor = "fortanix", target_env = "sgx")
+    ))]
+    pub extern "C" fn fmaf(x: f32, y: f32, z: f32) -> f32 {
+        soft_fma(x, y, z)
+    }
+
+    #[cfg(any(
+        all(target_arch = "arm", target_os = "none"),
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_os = "none",
+            not(target_feature = "d")
+        ),
+        all(target_arch = "wasm32", target_os = "unknown"),
+        all(target_vendor = "fortanix", target_env = "sgx")
+    ))]
+    pub extern "C" fn fma(x: f64, y: f64, z: f64) -> f64 {
+        soft_fma(x, y, z)
+    }
+}
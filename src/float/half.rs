@@ -0,0 +1,109 @@
+//! IEEE-754 binary16. Rust has no primitive for it, so `f16` is only a storage format here and its
+//! arithmetic is carried out in `f32`. The significand of `f32` is more than twice as wide as the
+//! one of `f16` plus two bits, so rounding twice gives the same results as rounding once.
+
+use core::{cmp, fmt, ops};
+
+use float::extend::extend;
+use float::trunc::trunc;
+
+/// A half precision float
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct f16(u16);
+
+impl f16 {
+    /// Raw transmutation from `u16`
+    pub const fn from_bits(bits: u16) -> f16 {
+        f16(bits)
+    }
+
+    /// Raw transmutation to `u16`
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Returns `true` if this value is NaN
+    pub const fn is_nan(self) -> bool {
+        self.0 & 0x7fff > 0x7c00
+    }
+
+    fn to_f32(self) -> f32 {
+        extend(self)
+    }
+
+    fn from_f32(a: f32) -> f16 {
+        trunc(a)
+    }
+}
+
+impl fmt::Debug for f16 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "f16({:#06x})", self.0)
+    }
+}
+
+impl PartialEq for f16 {
+    fn eq(&self, other: &f16) -> bool {
+        self.to_f32() == other.to_f32()
+    }
+}
+
+impl PartialOrd for f16 {
+    fn partial_cmp(&self, other: &f16) -> Option<cmp::Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
+    }
+}
+
+impl ops::Neg for f16 {
+    type Output = f16;
+
+    fn neg(self) -> f16 {
+        f16(self.0 ^ 0x8000)
+    }
+}
+
+impl ops::Add for f16 {
+    type Output = f16;
+
+    fn add(self, rhs: f16) -> f16 {
+        f16::from_f32(self.to_f32() + rhs.to_f32())
+    }
+}
+
+impl ops::Sub for f16 {
+    type Output = f16;
+
+    fn sub(self, rhs: f16) -> f16 {
+        f16::from_f32(self.to_f32() - rhs.to_f32())
+    }
+}
+
+impl ops::Mul for f16 {
+    type Output = f16;
+
+    fn mul(self, rhs: f16) -> f16 {
+        f16::from_f32(self.to_f32() * rhs.to_f32())
+    }
+}
+
+impl ops::Div for f16 {
+    type Output = f16;
+
+    fn div(self, rhs: f16) -> f16 {
+        f16::from_f32(self.to_f32() / rhs.to_f32())
+    }
+}
+
+impl ops::AddAssign for f16 {
+    fn add_assign(&mut self, rhs: f16) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::MulAssign for f16 {
+    fn mul_assign(&mut self, rhs: f16) {
+        *self = *self * rhs;
+    }
+}
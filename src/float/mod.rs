@@ -3,16 +3,35 @@ use core::ops;
 use super::int::Int;
 
 pub mod add;
+mod bfloat;
 pub mod cmp;
+pub mod complex;
 pub mod conv;
 pub mod div;
+pub mod double_double;
 pub mod extend;
+pub mod extended;
+pub mod fenv;
+pub mod fma;
+mod half;
 pub mod mul;
 pub mod pow;
+mod quad;
+pub mod round;
+mod soft;
+pub mod sqrt;
 pub mod sub;
+pub mod trunc;
+
+pub use self::bfloat::bf16;
+pub use self::extended::f80;
+pub use self::half::f16;
+pub use self::quad::f128;
+pub use self::soft::{SoftF32, SoftF64};
 
 public_test_dep! {
 /// Trait for some basic operations on floats
+#[const_trait]
 pub(crate) trait Float:
     Copy
     + core::fmt::Debug
@@ -20,19 +39,19 @@ pub(crate) trait Float:
     + PartialOrd
     + ops::AddAssign
     + ops::MulAssign
+    + ops::Mul<Output = Self>
     + ops::Add<Output = Self>
     + ops::Sub<Output = Self>
     + ops::Div<Output = Self>
-    + ops::Rem<Output = Self>
 {
     /// A uint of the same with as the float
-    type Int: Int;
+    type Int: ~const Int;
 
     /// A int of the same with as the float
-    type SignedInt: Int;
+    type SignedInt: ~const Int;
 
     /// An int capable of containing the exponent bits plus a sign bit. This is signed.
-    type ExpInt: Int;
+    type ExpInt: ~const Int;
 
     const ZERO: Self;
     const ONE: Self;
@@ -103,13 +122,25 @@ pub(crate) trait Float:
 
 macro_rules! float_impl {
     ($ty:ident, $ity:ident, $sity:ident, $expty:ident, $bits:expr, $significand_bits:expr) => {
-        impl Float for $ty {
+        float_impl!($ty, $ity, $sity, $expty, $bits, $significand_bits, 0.0, 1.0);
+    };
+    (
+        $ty:ident,
+        $ity:ident,
+        $sity:ident,
+        $expty:ident,
+        $bits:expr,
+        $significand_bits:expr,
+        $zero:expr,
+        $one:expr
+    ) => {
+        impl const Float for $ty {
             type Int = $ity;
             type SignedInt = $sity;
             type ExpInt = $expty;
 
-            const ZERO: Self = 0.0;
-            const ONE: Self = 1.0;
+            const ZERO: Self = $zero;
+            const ONE: Self = $one;
 
             const BITS: u32 = $bits;
             const SIGNIFICAND_BITS: u32 = $significand_bits;
@@ -170,5 +201,55 @@ macro_rules! float_impl {
     };
 }
 
+float_impl!(
+    bf16,
+    u16,
+    i16,
+    i16,
+    16,
+    7,
+    bf16::from_bits(0),
+    bf16::from_bits(0x3f80)
+);
+float_impl!(
+    f16,
+    u16,
+    i16,
+    i8,
+    16,
+    10,
+    f16::from_bits(0),
+    f16::from_bits(0x3c00)
+);
 float_impl!(f32, u32, i32, i16, 32, 23);
 float_impl!(f64, u64, i64, i16, 64, 52);
+float_impl!(
+    f128,
+    u128,
+    i128,
+    i32,
+    128,
+    112,
+    f128::from_bits(0),
+    f128::from_bits(0x3fff << 112)
+);
+float_impl!(
+    SoftF32,
+    u32,
+    i32,
+    i16,
+    32,
+    23,
+    SoftF32::from_bits(0),
+    SoftF32::from_bits(0x3f80_0000)
+);
+float_impl!(
+    SoftF64,
+    u64,
+    i64,
+    i16,
+    64,
+    52,
+    SoftF64::from_bits(0),
+    SoftF64::from_bits(0x3ff0 << 48)
+);
@@ -1,18 +1,20 @@
+use float::fenv::{self, FE_INEXACT, FE_INVALID, FE_UNDERFLOW};
+use float::round::{overflow, Round};
 use float::Float;
 use int::{CastInto, DInt, HInt, Int};
 
-fn mul<F: Float>(a: F, b: F) -> F
+/// Returns `a * b`, rounded in the direction `round`
+pub(crate) const fn mul<F: ~const Float>(a: F, b: F, round: Round) -> F
 where
-    u32: CastInto<F::Int>,
-    F::Int: CastInto<u32>,
-    i32: CastInto<F::Int>,
-    F::Int: CastInto<i32>,
-    F::Int: HInt,
+    u32: ~const CastInto<F::Int>,
+    F::Int: ~const CastInto<u32>,
+    i32: ~const CastInto<F::Int>,
+    F::Int: ~const CastInto<i32>,
+    F::Int: ~const HInt,
 {
     let one = F::Int::ONE;
     let zero = F::Int::ZERO;
 
-    let bits = F::BITS;
     let significand_bits = F::SIGNIFICAND_BITS;
     let max_exponent = F::EXPONENT_MAX;
 
@@ -26,7 +28,9 @@ where
     let inf_rep = exponent_mask;
     let quiet_bit = implicit_bit >> 1;
     let qnan_rep = exponent_mask | quiet_bit;
-    let exponent_bits = F::EXPONENT_BITS;
+    // The representation may be narrower than the integer, whose halves hold the product.
+    let width = F::Int::BITS;
+    let low_top_bit = one << (width - 1);
 
     let a_rep = a.repr();
     let b_rep = b.repr();
@@ -45,6 +49,8 @@ where
     {
         let a_abs = a_rep & abs_mask;
         let b_abs = b_rep & abs_mask;
+        fenv::raise_if_signaling::<F>(a_abs);
+        fenv::raise_if_signaling::<F>(b_abs);
 
         // NaN + anything = qNaN
         if a_abs > inf_rep {
@@ -61,6 +67,7 @@ where
                 return F::from_repr(a_abs | product_sign);
             } else {
                 // infinity * zero = NaN
+                fenv::raise(FE_INVALID);
                 return F::from_repr(qnan_rep);
             }
         }
@@ -71,6 +78,7 @@ where
                 return F::from_repr(b_abs | product_sign);
             } else {
                 // infinity * zero = NaN
+                fenv::raise(FE_INVALID);
                 return F::from_repr(qnan_rep);
             }
         }
@@ -108,12 +116,12 @@ where
     b_significand |= implicit_bit;
 
     // Get the significand of a*b.  Before multiplying the significands, shift
-    // one of them left to left-align it in the field.  Thus, the product will
-    // have (exponentBits + 2) integral digits, all but two of which must be
-    // zero.  Normalizing this result is just a conditional left-shift by one
-    // and bumping the exponent accordingly.
+    // one of them left to left-align it in the integer.  Thus, the high half of
+    // the product has the implicit bit in its place or one place below it.
+    // Normalizing this result is just a conditional left-shift by one and
+    // bumping the exponent accordingly.
     let (mut product_low, mut product_high) = a_significand
-        .widen_mul(b_significand << exponent_bits)
+        .widen_mul(b_significand << (width - significand_bits - 1))
         .lo_hi();
 
     let a_exponent_i32: i32 = a_exponent.cast();
@@ -127,40 +135,38 @@ where
     if (product_high & implicit_bit) != zero {
         product_exponent = product_exponent.wrapping_add(1);
     } else {
-        product_high = (product_high << 1) | (product_low >> (bits - 1));
+        product_high = (product_high << 1) | (product_low >> (width - 1));
         product_low <<= 1;
     }
 
-    // If we have overflowed the type, return +/- infinity.
+    // If we have overflowed the type, return +/- infinity (or the largest
+    // finite number, depending on the rounding direction).
     if product_exponent >= max_exponent as i32 {
-        return F::from_repr(inf_rep | product_sign);
+        return overflow(product_sign != zero, round);
     }
 
     if product_exponent <= 0 {
         // Result is denormal before rounding
         //
         // If the result is so small that it just underflows to zero, return
-        // a zero of the appropriate sign.  Mathematically there is no need to
+        // a zero of the appropriate sign (or the smallest denormal, when
+        // rounding away from zero).  Mathematically there is no need to
         // handle this case separately, but we make it a special case to
         // simplify the shift logic.
-        let shift = one.wrapping_sub(product_exponent.cast()).cast();
-        if shift >= bits {
+        let shift = (1 - product_exponent) as u32;
+        if shift >= width {
+            fenv::raise(FE_UNDERFLOW | FE_INEXACT);
+            if round.round_up(product_sign != zero, false, false, true) {
+                return F::from_repr(product_sign | one);
+            }
             return F::from_repr(product_sign);
         }
 
         // Otherwise, shift the significand of the result so that the round
         // bit is the high bit of productLo.
-        if shift < bits {
-            let sticky = product_low << (bits - shift);
-            product_low = product_high << (bits - shift) | product_low >> shift | sticky;
-            product_high >>= shift;
-        } else if shift < (2 * bits) {
-            let sticky = product_high << (2 * bits - shift) | product_low;
-            product_low = product_high >> (shift - bits) | sticky;
-            product_high = zero;
-        } else {
-            product_high = zero;
-        }
+        let sticky = F::Int::from_bool(product_low << (width - shift) != zero);
+        product_low = product_high << (width - shift) | product_low >> shift | sticky;
+        product_high >>= shift;
     } else {
         // Result is normal before rounding; insert the exponent.
         product_high &= significand_mask;
@@ -171,15 +177,20 @@ where
     product_high |= product_sign;
 
     // Final rounding.  The final result may overflow to infinity, or underflow
-    // to zero, but those are the correct results in those cases.  We use the
-    // default IEEE-754 round-to-nearest, ties-to-even rounding mode.
-    if product_low > sign_bit {
+    // to zero, but those are the correct results in those cases.
+    if round.round_up(
+        product_sign != zero,
+        product_high & one != zero,
+        product_low & low_top_bit != zero,
+        product_low & (low_top_bit - one) != zero,
+    ) {
         product_high += one;
     }
-
-    if product_low == sign_bit {
-        product_high += product_high & one;
-    }
+    fenv::raise_rounded(
+        product_low != zero,
+        product_exponent <= 0,
+        product_high & abs_mask >= inf_rep,
+    );
 
     F::from_repr(product_high)
 }
@@ -188,13 +199,13 @@ intrinsics! {
     #[aapcs_on_arm]
     #[arm_aeabi_alias = __aeabi_fmul]
     pub extern "C" fn __mulsf3(a: f32, b: f32) -> f32 {
-        mul(a, b)
+        mul(a, b, Round::Nearest)
     }
 
     #[aapcs_on_arm]
     #[arm_aeabi_alias = __aeabi_dmul]
     pub extern "C" fn __muldf3(a: f64, b: f64) -> f64 {
-        mul(a, b)
+        mul(a, b, Round::Nearest)
     }
 
     #[cfg(target_arch = "arm")]
@@ -207,3 +218,15 @@ intrinsics! {
         a * b
     }
 }
+
+#[cfg(any(
+    all(target_arch = "x86_64", not(windows)),
+    target_arch = "aarch64",
+    target_arch = "mips64",
+    target_arch = "riscv64"
+))]
+intrinsics! {
+    pub extern "C" fn __multf3(a: ::float::f128, b: ::float::f128) -> ::float::f128 {
+        mul(a, b, Round::Nearest)
+    }
+}
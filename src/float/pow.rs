@@ -2,7 +2,7 @@ use float::Float;
 use int::Int;
 
 /// Returns `a` raised to the power `b`
-fn pow<F: Float>(a: F, b: i32) -> F {
+pub(crate) fn pow<F: Float>(a: F, b: i32) -> F {
     let mut a = a;
     let recip = b < 0;
     let mut pow = i32::abs_diff(b, 0);
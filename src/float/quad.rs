@@ -0,0 +1,169 @@
+//! IEEE-754 binary128, the format of `long double` on AArch64, 64-bit MIPS and 64-bit RISC-V and
+//! of `__float128` on x86_64. Rust has no primitive for it, so all arithmetic on `f128` goes
+//! through the soft-float routines of this crate.
+
+use core::{cmp, fmt, ops};
+
+pub use self::repr::f128;
+use float::round::Round;
+use float::{add, div, mul};
+
+// On these targets `long double` and `__float128` are passed around in vector registers, so a
+// SIMD type is used to give `f128` the same C ABI.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+mod repr {
+    /// A quadruple precision float
+    #[allow(non_camel_case_types)]
+    #[derive(Clone, Copy)]
+    #[repr(simd)]
+    pub struct f128([u64; 2]);
+
+    impl f128 {
+        /// Raw transmutation from `u128`
+        pub const fn from_bits(bits: u128) -> f128 {
+            // The lanes are in memory order, so the high half comes first on big endian targets
+            if cfg!(target_endian = "little") {
+                f128([bits as u64, (bits >> 64) as u64])
+            } else {
+                f128([(bits >> 64) as u64, bits as u64])
+            }
+        }
+
+        /// Raw transmutation to `u128`
+        pub const fn to_bits(self) -> u128 {
+            // The lanes of a SIMD type cannot be read directly, but its memory layout matches
+            // the one of `u128`.
+            unsafe { core::mem::transmute::<f128, u128>(self) }
+        }
+    }
+}
+
+// The n64 ABI passes `long double` in a pair of floating-point registers, the same way as a
+// structure of two `double`s.
+#[cfg(target_arch = "mips64")]
+mod repr {
+    /// A quadruple precision float
+    #[allow(non_camel_case_types)]
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct f128(f64, f64);
+
+    impl f128 {
+        /// Raw transmutation from `u128`
+        pub const fn from_bits(bits: u128) -> f128 {
+            unsafe { core::mem::transmute::<u128, f128>(bits) }
+        }
+
+        /// Raw transmutation to `u128`
+        pub const fn to_bits(self) -> u128 {
+            unsafe { core::mem::transmute::<f128, u128>(self) }
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_arch = "x86_64",
+    target_arch = "aarch64",
+    target_arch = "mips64"
+)))]
+mod repr {
+    /// A quadruple precision float
+    #[allow(non_camel_case_types)]
+    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    pub struct f128(u128);
+
+    impl f128 {
+        /// Raw transmutation from `u128`
+        pub const fn from_bits(bits: u128) -> f128 {
+            f128(bits)
+        }
+
+        /// Raw transmutation to `u128`
+        pub const fn to_bits(self) -> u128 {
+            self.0
+        }
+    }
+}
+
+impl f128 {
+    /// Returns `true` if this value is NaN
+    pub const fn is_nan(self) -> bool {
+        self.to_bits() & !(1 << 127) > 0x7fff << 112
+    }
+}
+
+impl fmt::Debug for f128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "f128({:#034x})", self.to_bits())
+    }
+}
+
+impl PartialEq for f128 {
+    fn eq(&self, other: &f128) -> bool {
+        self.partial_cmp(other) == Some(cmp::Ordering::Equal)
+    }
+}
+
+impl PartialOrd for f128 {
+    fn partial_cmp(&self, other: &f128) -> Option<cmp::Ordering> {
+        match super::cmp::cmp(*self, *other) {
+            super::cmp::Result::Less => Some(cmp::Ordering::Less),
+            super::cmp::Result::Equal => Some(cmp::Ordering::Equal),
+            super::cmp::Result::Greater => Some(cmp::Ordering::Greater),
+            super::cmp::Result::Unordered => None,
+        }
+    }
+}
+
+impl ops::Neg for f128 {
+    type Output = f128;
+
+    fn neg(self) -> f128 {
+        f128::from_bits(self.to_bits() ^ (1 << 127))
+    }
+}
+
+impl ops::Add for f128 {
+    type Output = f128;
+
+    fn add(self, rhs: f128) -> f128 {
+        add::add(self, rhs, Round::Nearest)
+    }
+}
+
+impl ops::Sub for f128 {
+    type Output = f128;
+
+    fn sub(self, rhs: f128) -> f128 {
+        add::add(self, -rhs, Round::Nearest)
+    }
+}
+
+impl ops::Mul for f128 {
+    type Output = f128;
+
+    fn mul(self, rhs: f128) -> f128 {
+        mul::mul(self, rhs, Round::Nearest)
+    }
+}
+
+impl ops::Div for f128 {
+    type Output = f128;
+
+    fn div(self, rhs: f128) -> f128 {
+        div::div(self, rhs, Round::Nearest)
+    }
+}
+
+impl ops::AddAssign for f128 {
+    fn add_assign(&mut self, rhs: f128) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::MulAssign for f128 {
+    fn mul_assign(&mut self, rhs: f128) {
+        *self = *self * rhs;
+    }
+}
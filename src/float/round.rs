@@ -0,0 +1,181 @@
+//! Arithmetic with a rounding direction other than the IEEE-754 default.
+//!
+//! The intrinsics always round to nearest, ties to even, but the generic routines behind them take
+//! the rounding direction as a parameter. The functions of this module expose those routines, so
+//! that e.g. interval arithmetic can be done on targets without hardware floating point:
+//! `add_rounded::<f64>(a, b, Round::Down)` and `add_rounded::<f64>(a, b, Round::Up)` bound the
+//! exact sum of `a` and `b` from below and above.
+
+use float::fenv::{self, FE_INEXACT, FE_OVERFLOW};
+use float::{add, conv, div, f128, mul, Float};
+use int::Int;
+
+/// The direction in which a result that is not exactly representable is rounded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Round {
+    /// Round to the nearest representable value, and to the one with an even significand on ties
+    Nearest,
+    /// Round toward positive infinity
+    Up,
+    /// Round toward negative infinity
+    Down,
+    /// Round toward zero
+    Zero,
+}
+
+impl Round {
+    /// Returns `true` if a result whose magnitude was truncated has to be incremented by one ulp.
+    /// `half` is the most significant bit that was truncated and `sticky` the "or" of all the
+    /// ones below it.
+    pub(crate) const fn round_up(
+        self,
+        negative: bool,
+        odd: bool,
+        half: bool,
+        sticky: bool,
+    ) -> bool {
+        match self {
+            Round::Nearest => half && (sticky || odd),
+            Round::Up => !negative && (half || sticky),
+            Round::Down => negative && (half || sticky),
+            Round::Zero => false,
+        }
+    }
+}
+
+/// Returns the result of an operation whose magnitude is too large for `F`, which is either an
+/// infinity or the largest finite value depending on the rounding direction, and raises the
+/// overflow flag
+pub(crate) const fn overflow<F: ~const Float>(negative: bool, round: Round) -> F {
+    fenv::raise(FE_OVERFLOW | FE_INEXACT);
+    let to_infinity = match round {
+        Round::Nearest => true,
+        Round::Up => !negative,
+        Round::Down => negative,
+        Round::Zero => false,
+    };
+    let abs = if to_infinity {
+        F::EXPONENT_MASK
+    } else {
+        F::EXPONENT_MASK - F::Int::ONE
+    };
+    F::from_repr(if negative { abs | F::SIGN_MASK } else { abs })
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+    impl Sealed for super::f128 {}
+}
+
+/// Floating-point types that support arithmetic with a rounding direction
+pub trait RoundedFloat: Copy + sealed::Sealed {
+    #[doc(hidden)]
+    fn add(a: Self, b: Self, round: Round) -> Self;
+    #[doc(hidden)]
+    fn mul(a: Self, b: Self, round: Round) -> Self;
+    #[doc(hidden)]
+    fn div(a: Self, b: Self, round: Round) -> Self;
+    #[doc(hidden)]
+    fn neg(a: Self) -> Self;
+}
+
+impl RoundedFloat for f32 {
+    fn add(a: f32, b: f32, round: Round) -> f32 {
+        add::add(a, b, round)
+    }
+    fn mul(a: f32, b: f32, round: Round) -> f32 {
+        mul::mul(a, b, round)
+    }
+    fn div(a: f32, b: f32, round: Round) -> f32 {
+        div::div(a, b, round)
+    }
+    fn neg(a: f32) -> f32 {
+        f32::from_repr(a.repr() ^ f32::SIGN_MASK)
+    }
+}
+
+impl RoundedFloat for f64 {
+    fn add(a: f64, b: f64, round: Round) -> f64 {
+        add::add(a, b, round)
+    }
+    fn mul(a: f64, b: f64, round: Round) -> f64 {
+        mul::mul(a, b, round)
+    }
+    fn div(a: f64, b: f64, round: Round) -> f64 {
+        div::div(a, b, round)
+    }
+    fn neg(a: f64) -> f64 {
+        f64::from_repr(a.repr() ^ f64::SIGN_MASK)
+    }
+}
+
+impl RoundedFloat for f128 {
+    fn add(a: f128, b: f128, round: Round) -> f128 {
+        add::add(a, b, round)
+    }
+    fn mul(a: f128, b: f128, round: Round) -> f128 {
+        mul::mul(a, b, round)
+    }
+    fn div(a: f128, b: f128, round: Round) -> f128 {
+        div::div(a, b, round)
+    }
+    fn neg(a: f128) -> f128 {
+        -a
+    }
+}
+
+/// Integer types that can be converted to the float type `F` with a rounding direction
+pub trait RoundedIntoFloat<F>: Copy + sealed::Sealed {
+    #[doc(hidden)]
+    fn into_float(self, round: Round) -> F;
+}
+
+macro_rules! rounded_into_float {
+    ($($ity:ident),*) => {
+        $(
+            impl sealed::Sealed for $ity {}
+
+            impl RoundedIntoFloat<f32> for $ity {
+                fn into_float(self, round: Round) -> f32 {
+                    conv::int_to_float(self, round)
+                }
+            }
+
+            impl RoundedIntoFloat<f64> for $ity {
+                fn into_float(self, round: Round) -> f64 {
+                    conv::int_to_float(self, round)
+                }
+            }
+        )*
+    };
+}
+
+rounded_into_float!(i32, u32, i64, u64, i128, u128);
+
+/// Returns `a + b`, rounded in the direction `round`
+pub fn add_rounded<F: RoundedFloat>(a: F, b: F, round: Round) -> F {
+    F::add(a, b, round)
+}
+
+/// Returns `a - b`, rounded in the direction `round`
+pub fn sub_rounded<F: RoundedFloat>(a: F, b: F, round: Round) -> F {
+    F::add(a, F::neg(b), round)
+}
+
+/// Returns `a * b`, rounded in the direction `round`
+pub fn mul_rounded<F: RoundedFloat>(a: F, b: F, round: Round) -> F {
+    F::mul(a, b, round)
+}
+
+/// Returns `a / b`, rounded in the direction `round`
+pub fn div_rounded<F: RoundedFloat>(a: F, b: F, round: Round) -> F {
+    F::div(a, b, round)
+}
+
+/// Returns `i` converted to `F`, rounded in the direction `round`
+pub fn int_to_float_rounded<I: RoundedIntoFloat<F>, F>(i: I, round: Round) -> F {
+    i.into_float(round)
+}
@@ -0,0 +1,235 @@
+//! IEEE-754 binary32 and binary64 that are always computed by the soft-float routines of this
+//! crate, even on targets with a floating point unit. Hardware implementations differ in details
+//! like the handling of denormals or the intermediate precision of x87, so these types are meant
+//! for code that needs results which are bit for bit the same on every target.
+
+use core::{cmp, fmt, ops};
+
+use float::conv::{float_to_int, int_to_float};
+use float::extend::extend;
+use float::fma::soft_fma;
+use float::round::Round;
+use float::sqrt::soft_sqrt;
+use float::trunc::trunc;
+use float::{add, div, mul, Float};
+
+macro_rules! soft_float {
+    (
+        $(#[$attr:meta])*
+        $ty:ident, $fty:ident, $ity:ident;
+        $($from:ident, $to:ident, $int:ident;)*
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy)]
+        #[repr(transparent)]
+        pub struct $ty($ity);
+
+        impl $ty {
+            /// Raw transmutation from the bits of the float
+            pub const fn from_bits(bits: $ity) -> $ty {
+                $ty(bits)
+            }
+
+            /// Raw transmutation to the bits of the float
+            pub const fn to_bits(self) -> $ity {
+                self.0
+            }
+
+            /// Returns `true` if this value is NaN
+            pub const fn is_nan(self) -> bool {
+                self.0 & !$ty::SIGN_MASK > $ty::EXPONENT_MASK
+            }
+
+            /// Returns `-self`. Unlike the operator, this can be used in const contexts.
+            pub const fn neg(self) -> $ty {
+                $ty(self.0 ^ $ty::SIGN_MASK)
+            }
+
+            /// Returns `self + rhs`. Unlike the operator, this can be used in const contexts.
+            pub const fn add(self, rhs: $ty) -> $ty {
+                add::add(self, rhs, Round::Nearest)
+            }
+
+            /// Returns `self - rhs`. Unlike the operator, this can be used in const contexts.
+            pub const fn sub(self, rhs: $ty) -> $ty {
+                self.add(rhs.neg())
+            }
+
+            /// Returns `self * rhs`. Unlike the operator, this can be used in const contexts.
+            pub const fn mul(self, rhs: $ty) -> $ty {
+                mul::mul(self, rhs, Round::Nearest)
+            }
+
+            /// Returns `self / rhs`. Unlike the operator, this can be used in const contexts.
+            pub const fn div(self, rhs: $ty) -> $ty {
+                div::div(self, rhs, Round::Nearest)
+            }
+
+            /// Returns the square root of `self`, like `sqrt` of the primitive floats
+            pub fn sqrt(self) -> $ty {
+                soft_sqrt(self)
+            }
+
+            /// Returns `self * a + b` rounded only once, like `mul_add` of the primitive floats
+            pub fn mul_add(self, a: $ty, b: $ty) -> $ty {
+                soft_fma(self, a, b)
+            }
+
+            $(
+                /// Converts `i` like an `as` cast does, rounding to nearest
+                pub const fn $from(i: $int) -> $ty {
+                    int_to_float(i, Round::Nearest)
+                }
+
+                /// Converts `self` like an `as` cast does, truncating toward zero, saturating on
+                /// overflow and mapping NaN to zero
+                pub const fn $to(self) -> $int {
+                    float_to_int(self)
+                }
+            )*
+        }
+
+        impl From<$fty> for $ty {
+            fn from(a: $fty) -> $ty {
+                $ty(a.to_bits())
+            }
+        }
+
+        impl From<$ty> for $fty {
+            fn from(a: $ty) -> $fty {
+                $fty::from_bits(a.0)
+            }
+        }
+
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                // The two hex digits per byte and the `0x` prefix
+                let width = 2 + 2 * core::mem::size_of::<$ity>();
+                write!(f, concat!(stringify!($ty), "({:#0w$x})"), self.0, w = width)
+            }
+        }
+
+        impl PartialEq for $ty {
+            fn eq(&self, other: &$ty) -> bool {
+                self.partial_cmp(other) == Some(cmp::Ordering::Equal)
+            }
+        }
+
+        impl PartialOrd for $ty {
+            fn partial_cmp(&self, other: &$ty) -> Option<cmp::Ordering> {
+                match super::cmp::cmp(*self, *other) {
+                    super::cmp::Result::Less => Some(cmp::Ordering::Less),
+                    super::cmp::Result::Equal => Some(cmp::Ordering::Equal),
+                    super::cmp::Result::Greater => Some(cmp::Ordering::Greater),
+                    super::cmp::Result::Unordered => None,
+                }
+            }
+        }
+
+        impl ops::Neg for $ty {
+            type Output = $ty;
+
+            fn neg(self) -> $ty {
+                $ty::neg(self)
+            }
+        }
+
+        impl ops::Add for $ty {
+            type Output = $ty;
+
+            fn add(self, rhs: $ty) -> $ty {
+                $ty::add(self, rhs)
+            }
+        }
+
+        impl ops::Sub for $ty {
+            type Output = $ty;
+
+            fn sub(self, rhs: $ty) -> $ty {
+                $ty::sub(self, rhs)
+            }
+        }
+
+        impl ops::Mul for $ty {
+            type Output = $ty;
+
+            fn mul(self, rhs: $ty) -> $ty {
+                $ty::mul(self, rhs)
+            }
+        }
+
+        impl ops::Div for $ty {
+            type Output = $ty;
+
+            fn div(self, rhs: $ty) -> $ty {
+                $ty::div(self, rhs)
+            }
+        }
+
+        impl ops::AddAssign for $ty {
+            fn add_assign(&mut self, rhs: $ty) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ops::SubAssign for $ty {
+            fn sub_assign(&mut self, rhs: $ty) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl ops::MulAssign for $ty {
+            fn mul_assign(&mut self, rhs: $ty) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl ops::DivAssign for $ty {
+            fn div_assign(&mut self, rhs: $ty) {
+                *self = *self / rhs;
+            }
+        }
+    };
+}
+
+soft_float! {
+    /// A single precision float whose arithmetic is done in software
+    SoftF32, f32, u32;
+    from_i32, to_i32, i32;
+    from_u32, to_u32, u32;
+    from_i64, to_i64, i64;
+    from_u64, to_u64, u64;
+    from_i128, to_i128, i128;
+    from_u128, to_u128, u128;
+}
+
+soft_float! {
+    /// A double precision float whose arithmetic is done in software
+    SoftF64, f64, u64;
+    from_i32, to_i32, i32;
+    from_u32, to_u32, u32;
+    from_i64, to_i64, i64;
+    from_u64, to_u64, u64;
+    from_i128, to_i128, i128;
+    from_u128, to_u128, u128;
+}
+
+impl SoftF64 {
+    /// Converts `self` like `x as f32` does, rounding to nearest
+    pub fn to_soft_f32(self) -> SoftF32 {
+        trunc(self)
+    }
+}
+
+impl SoftF32 {
+    /// Converts `self` like `x as f64` does, which is always exact
+    pub const fn to_soft_f64(self) -> SoftF64 {
+        extend(self)
+    }
+}
+
+impl From<SoftF32> for SoftF64 {
+    fn from(a: SoftF32) -> SoftF64 {
+        a.to_soft_f64()
+    }
+}
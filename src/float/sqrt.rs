@@ -0,0 +1,135 @@
+use float::fenv::{self, FE_INEXACT, FE_INVALID};
+use float::Float;
+use int::{CastInto, Int};
+
+public_test_dep! {
+/// Returns the square root of `x`, correctly rounded to nearest, ties to even
+///
+/// The root is generated one bit at a time like in a long division by hand, which leaves the exact
+/// remainder to decide the rounding with. This is the method of the `e_sqrt.c` of fdlibm.
+pub(crate) fn soft_sqrt<F: Float>(x: F) -> F
+where
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<u32>,
+{
+    let one = F::Int::ONE;
+    let zero = F::Int::ZERO;
+
+    let significand_bits = F::SIGNIFICAND_BITS;
+    let exponent_bias = F::EXPONENT_BIAS as i32;
+
+    let implicit_bit = F::IMPLICIT_BIT;
+    let significand_mask = F::SIGNIFICAND_MASK;
+    let sign_bit = F::SIGN_MASK as F::Int;
+    let abs_mask = sign_bit - one;
+    let exponent_mask = F::EXPONENT_MASK;
+    let inf_rep = exponent_mask;
+    let quiet_bit = implicit_bit >> 1;
+    let qnan_rep = exponent_mask | quiet_bit;
+
+    let x_rep = x.repr();
+    let x_abs = x_rep & abs_mask;
+
+    // sqrt(+/- 0) = +/- 0
+    if x_abs == zero {
+        return x;
+    }
+
+    // sqrt(NaN) = qNaN
+    if x_abs > inf_rep {
+        fenv::raise_if_signaling::<F>(x_abs);
+        return F::from_repr(x_rep | quiet_bit);
+    }
+
+    // sqrt(negative) = NaN
+    if x_rep & sign_bit != zero {
+        fenv::raise(FE_INVALID);
+        return F::from_repr(qnan_rep);
+    }
+
+    // sqrt(infinity) = infinity
+    if x_abs == inf_rep {
+        return x;
+    }
+
+    // Split x into an integer significand with the implicit bit set and an unbiased exponent,
+    // renormalizing denormals.
+    let (exponent, mut significand) = if x_abs < implicit_bit {
+        F::normalize(x_abs)
+    } else {
+        (
+            CastInto::<u32>::cast(x_abs >> significand_bits) as i32,
+            (x_abs & significand_mask) | implicit_bit,
+        )
+    };
+    let mut exponent = exponent - exponent_bias;
+
+    // Make the exponent even, so that it can be halved exactly.
+    if exponent & 1 != 0 {
+        significand <<= 1;
+    }
+    exponent >>= 1;
+
+    // Generate the root one bit at a time. `root` gets the significand bits of the result and the
+    // one below them, and `significand` keeps the remainder.
+    significand <<= 1;
+    let mut root = zero;
+    let mut partial = zero;
+    let mut bit = implicit_bit << 1;
+    while bit != zero {
+        let trial = partial + bit;
+        if trial <= significand {
+            partial = trial + bit;
+            significand -= trial;
+            root += bit;
+        }
+        significand <<= 1;
+        bit >>= 1;
+    }
+
+    // The exact root is never halfway between two floats, so it rounds up exactly when the bit
+    // below the significand is set. The result cannot overflow or underflow.
+    let round_bit = root & one;
+    if significand != zero || round_bit != zero {
+        fenv::raise(FE_INEXACT);
+    }
+    let root = (root >> 1) + round_bit;
+
+    // `root` has the implicit bit set, which adds one to the exponent.
+    let exponent: F::Int = ((exponent + exponent_bias - 1) as u32).cast();
+    F::from_repr(root + (exponent << significand_bits))
+}
+}
+
+// LLVM lowers `f32::sqrt` and `f64::sqrt` to these on targets without instructions for them. Only
+// the targets without a libm get them here, where `math` provides the rest of it. Elsewhere they
+// come from the libm of the system, which they must not shadow.
+intrinsics! {
+    #[cfg(any(
+        all(target_arch = "arm", target_os = "none"),
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_os = "none",
+            not(target_feature = "f")
+        ),
+        all(target_arch = "wasm32", target_os = "unknown"),
+        all(target_vendor = "fortanix", target_env = "sgx")
+    ))]
+    pub extern "C" fn sqrtf(x: f32) -> f32 {
+        soft_sqrt(x)
+    }
+
+    #[cfg(any(
+        all(target_arch = "arm", target_os = "none"),
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            target_os = "none",
+            not(target_feature = "d")
+        ),
+        all(target_arch = "wasm32", target_os = "unknown"),
+        all(target_vendor = "fortanix", target_env = "sgx")
+    ))]
+    pub extern "C" fn sqrt(x: f64) -> f64 {
+        soft_sqrt(x)
+    }
+}
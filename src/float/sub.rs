@@ -13,6 +13,16 @@ intrinsics! {
         __adddf3(a, f64::from_repr(b.repr() ^ f64::SIGN_MASK))
     }
 
+    #[cfg(target_arch = "arm")]
+    pub extern "aapcs" fn __aeabi_frsub(a: f32, b: f32) -> f32 {
+        __subsf3(b, a)
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "aapcs" fn __aeabi_drsub(a: f64, b: f64) -> f64 {
+        __subdf3(b, a)
+    }
+
     #[cfg(target_arch = "arm")]
     pub extern "C" fn __subsf3vfp(a: f32, b: f32) -> f32 {
         a - b
@@ -22,4 +32,26 @@ intrinsics! {
     pub extern "C" fn __subdf3vfp(a: f64, b: f64) -> f64 {
         a - b
     }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __negsf2vfp(a: f32) -> f32 {
+        -a
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __negdf2vfp(a: f64) -> f64 {
+        -a
+    }
+}
+
+#[cfg(any(
+    all(target_arch = "x86_64", not(windows)),
+    target_arch = "aarch64",
+    target_arch = "mips64",
+    target_arch = "riscv64"
+))]
+intrinsics! {
+    pub extern "C" fn __subtf3(a: ::float::f128, b: ::float::f128) -> ::float::f128 {
+        super::add::__addtf3(a, -b)
+    }
 }
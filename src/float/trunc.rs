@@ -0,0 +1,160 @@
+use float::fenv::{self, FE_INEXACT, FE_OVERFLOW};
+use float::Float;
+use int::{CastInto, Int};
+
+/// Generic conversion from a wider to a narrower IEEE-754 floating-point type, rounding to nearest
+/// with ties to even
+pub(crate) fn trunc<F: Float, R: Float>(a: F) -> R
+where
+    F::Int: CastInto<u32>,
+    u32: CastInto<F::Int>,
+    F::Int: CastInto<R::Int>,
+{
+    let src_zero = F::Int::ZERO;
+    let src_one = F::Int::ONE;
+    let src_bits = F::BITS;
+    let src_sign_bits = F::SIGNIFICAND_BITS;
+    let src_exp_bias = F::EXPONENT_BIAS;
+    let src_min_normal = F::IMPLICIT_BIT;
+    let src_significand_mask = F::SIGNIFICAND_MASK;
+    let src_infinity = F::EXPONENT_MASK;
+    let src_sign_mask = F::SIGN_MASK;
+    let src_abs_mask = src_sign_mask - src_one;
+    let src_qnan = src_one << (src_sign_bits - 1);
+    let src_nan_code = src_qnan - src_one;
+
+    let dst_bits = R::BITS;
+    let dst_sign_bits = R::SIGNIFICAND_BITS;
+    let dst_inf_exp = R::EXPONENT_MAX;
+    let dst_exp_bias = R::EXPONENT_BIAS;
+
+    let sign_bits_delta = src_sign_bits - dst_sign_bits;
+    let exp_bias_delta = src_exp_bias - dst_exp_bias;
+    let round_mask = (src_one << sign_bits_delta) - src_one;
+    let halfway = src_one << (sign_bits_delta - 1);
+
+    // The range of source exponents that are normal in the destination type
+    let underflow_exponent: F::Int = (exp_bias_delta + 1).cast();
+    let overflow_exponent: F::Int = (exp_bias_delta + dst_inf_exp).cast();
+    let underflow = underflow_exponent << src_sign_bits;
+    let overflow = overflow_exponent << src_sign_bits;
+
+    let a_abs = a.repr() & src_abs_mask;
+    let sign = a.repr() & src_sign_mask;
+    let abs_result: F::Int;
+
+    if a_abs.wrapping_sub(underflow) < a_abs.wrapping_sub(overflow) {
+        // The exponent of a is within the range of normal numbers in the
+        // destination format. We can convert by simply right-shifting with
+        // rounding and adjusting the exponent.
+        let bias_delta: F::Int = exp_bias_delta.cast();
+        let mut result = (a_abs >> sign_bits_delta) - (bias_delta << dst_sign_bits);
+        let round_bits = a_abs & round_mask;
+        if round_bits > halfway {
+            // Round to nearest.
+            result += src_one;
+        } else if round_bits == halfway {
+            // Tie to even.
+            result += result & src_one;
+        }
+        abs_result = result;
+        let dst_abs: R::Int = abs_result.cast();
+        fenv::raise_rounded(round_bits != src_zero, false, dst_abs >= R::EXPONENT_MASK);
+    } else if a_abs > src_infinity {
+        // a is NaN.
+        // Conjure the result by beginning with infinity, setting the qNaN
+        // bit and inserting the (truncated) trailing NaN field.
+        fenv::raise_if_signaling::<F>(a_abs);
+        let inf_exp: F::Int = dst_inf_exp.cast();
+        let dst_qnan = src_one << (dst_sign_bits - 1);
+        let dst_nan_code = dst_qnan - src_one;
+        abs_result = (inf_exp << dst_sign_bits)
+            | dst_qnan
+            | ((a_abs & src_nan_code) >> sign_bits_delta) & dst_nan_code;
+    } else if a_abs >= overflow {
+        // a overflows to infinity.
+        if a_abs != src_infinity {
+            fenv::raise(FE_OVERFLOW | FE_INEXACT);
+        }
+        let inf_exp: F::Int = dst_inf_exp.cast();
+        abs_result = inf_exp << dst_sign_bits;
+    } else {
+        // a underflows on conversion to the destination type or is an exact
+        // zero. The result may be a denormal or zero. Extract the exponent
+        // to get the shift amount for the denormalization. A denormal a has
+        // the exponent of the smallest normal numbers and no implicit bit.
+        let a_exp: u32 = (a_abs >> src_sign_bits).cast();
+        let (shift, significand) = if a_exp == 0 {
+            (exp_bias_delta, a.repr() & src_significand_mask)
+        } else {
+            (
+                exp_bias_delta - a_exp + 1,
+                (a.repr() & src_significand_mask) | src_min_normal,
+            )
+        };
+
+        // Right shift by the denormalization amount with sticky.
+        if shift > src_sign_bits {
+            abs_result = src_zero;
+            fenv::raise_rounded(a_abs != src_zero, true, false);
+        } else {
+            let sticky =
+                F::Int::from_bool(shift != 0 && significand << (src_bits - shift) != src_zero);
+            let denormalized_significand = significand >> shift | sticky;
+            let mut result = denormalized_significand >> sign_bits_delta;
+            let round_bits = denormalized_significand & round_mask;
+            if round_bits > halfway {
+                // Round to nearest.
+                result += src_one;
+            } else if round_bits == halfway {
+                // Tie to even.
+                result += result & src_one;
+            }
+            abs_result = result;
+            fenv::raise_rounded(round_bits != src_zero, true, false);
+        }
+    }
+
+    // Apply the signbit to the (dst_t)abs(a).
+    R::from_repr((abs_result | (sign >> (src_bits - dst_bits))).cast())
+}
+
+intrinsics! {
+    #[aapcs_on_arm]
+    #[arm_aeabi_alias = __aeabi_f2h]
+    pub extern "C" fn __truncsfhf2(a: f32) -> ::float::f16 {
+        trunc(a)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __gnu_f2h_ieee(a: f32) -> ::float::f16 {
+        trunc(a)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __truncsfbf2(a: f32) -> ::float::bf16 {
+        trunc(a)
+    }
+
+    #[aapcs_on_arm]
+    #[arm_aeabi_alias = __aeabi_d2f]
+    pub extern "C" fn __truncdfsf2(a: f64) -> f32 {
+        trunc(a)
+    }
+
+    #[cfg(target_arch = "arm")]
+    pub extern "C" fn __truncdfsf2vfp(a: f64) -> f32 {
+        a as f32 // LLVM generate 'fcvtsd'
+    }
+
+    #[aapcs_on_arm]
+    #[arm_aeabi_alias = __aeabi_d2h]
+    pub extern "C" fn __truncdfhf2(a: f64) -> ::float::f16 {
+        trunc(a)
+    }
+
+    #[aapcs_on_arm]
+    pub extern "C" fn __truncdfbf2(a: f64) -> ::float::bf16 {
+        trunc(a)
+    }
+}
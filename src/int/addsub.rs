@@ -1,6 +1,6 @@
 use int::{DInt, Int};
 
-trait UAddSub: DInt {
+pub(crate) trait UAddSub: DInt {
     fn uadd(self, other: Self) -> Self {
         let (lo, carry) = self.lo().overflowing_add(other.lo());
         let hi = self.hi().wrapping_add(other.hi());
@@ -20,7 +20,7 @@ trait UAddSub: DInt {
 
 impl UAddSub for u128 {}
 
-trait AddSub: Int
+pub(crate) trait AddSub: Int
 where
     <Self as Int>::UnsignedInt: UAddSub,
 {
@@ -35,7 +35,7 @@ where
 impl AddSub for u128 {}
 impl AddSub for i128 {}
 
-trait Addo: AddSub
+pub(crate) trait Addo: AddSub
 where
     <Self as Int>::UnsignedInt: UAddSub,
 {
@@ -48,7 +48,7 @@ where
 impl Addo for i128 {}
 impl Addo for u128 {}
 
-trait Subo: AddSub
+pub(crate) trait Subo: AddSub
 where
     <Self as Int>::UnsignedInt: UAddSub,
 {
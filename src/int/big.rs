@@ -0,0 +1,473 @@
+//! 256-bit integers. These are not used by any intrinsic directly, but they are the double width
+//! integers of `u128`, which the generic `f128` routines need for their widening multiplications.
+
+use core::ops;
+
+use int::{DInt, HInt, Int};
+
+public_test_dep! {
+/// A 256-bit unsigned integer made out of two `u128` halves
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct u256 {
+    pub lo: u128,
+    pub hi: u128,
+}
+}
+
+public_test_dep! {
+/// A 256-bit two's complement signed integer made out of two `u128` halves
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct i256 {
+    pub lo: u128,
+    pub hi: u128,
+}
+}
+
+impl PartialOrd for u256 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some((self.hi, self.lo).cmp(&(other.hi, other.lo)))
+    }
+}
+
+impl PartialOrd for i256 {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some((self.hi as i128, self.lo).cmp(&(other.hi as i128, other.lo)))
+    }
+}
+
+/// `u128` widening multiplication built out of `u64` widening multiplications
+const fn u128_widen_mul(lhs: u128, rhs: u128) -> u256 {
+    let ll = lhs.lo().zero_widen_mul(rhs.lo());
+    let lh = lhs.lo().zero_widen_mul(rhs.hi());
+    let hl = lhs.hi().zero_widen_mul(rhs.lo());
+    let hh = lhs.hi().zero_widen_mul(rhs.hi());
+    // this cannot overflow, the sum is at most `3 * (2^64 - 1)`
+    let mid = ll.hi().zero_widen() + lh.lo().zero_widen() + hl.lo().zero_widen();
+    u256 {
+        lo: u128::from_lo_hi(ll.lo(), mid.lo()),
+        hi: hh
+            .wrapping_add(lh.hi().zero_widen())
+            .wrapping_add(hl.hi().zero_widen())
+            .wrapping_add(mid.hi().zero_widen()),
+    }
+}
+
+/// Restoring long division, only used to satisfy the `Div` bound of `Int`
+fn u256_div_rem(duo: u256, div: u256) -> (u256, u256) {
+    let mut quo = u256::ZERO;
+    let mut rem = u256::ZERO;
+    let mut i = 256;
+    while i > 0 {
+        i -= 1;
+        rem = rem << 1 | (duo >> i & u256::ONE);
+        quo = quo << 1;
+        if rem >= div {
+            rem = rem.wrapping_sub(div);
+            quo |= u256::ONE;
+        }
+    }
+    (quo, rem)
+}
+
+macro_rules! impl_common {
+    ($ty:ident) => {
+        impl ops::Add for $ty {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                self.wrapping_add(rhs)
+            }
+        }
+
+        impl ops::Sub for $ty {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                self.wrapping_sub(rhs)
+            }
+        }
+
+        impl ops::AddAssign for $ty {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl ops::SubAssign for $ty {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl ops::Not for $ty {
+            type Output = Self;
+
+            fn not(self) -> Self {
+                $ty {
+                    lo: !self.lo,
+                    hi: !self.hi,
+                }
+            }
+        }
+
+        impl ops::BitAnd for $ty {
+            type Output = Self;
+
+            fn bitand(self, rhs: Self) -> Self {
+                $ty {
+                    lo: self.lo & rhs.lo,
+                    hi: self.hi & rhs.hi,
+                }
+            }
+        }
+
+        impl ops::BitOr for $ty {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                $ty {
+                    lo: self.lo | rhs.lo,
+                    hi: self.hi | rhs.hi,
+                }
+            }
+        }
+
+        impl ops::BitXor for $ty {
+            type Output = Self;
+
+            fn bitxor(self, rhs: Self) -> Self {
+                $ty {
+                    lo: self.lo ^ rhs.lo,
+                    hi: self.hi ^ rhs.hi,
+                }
+            }
+        }
+
+        impl ops::BitAndAssign for $ty {
+            fn bitand_assign(&mut self, rhs: Self) {
+                *self = *self & rhs;
+            }
+        }
+
+        impl ops::BitOrAssign for $ty {
+            fn bitor_assign(&mut self, rhs: Self) {
+                *self = *self | rhs;
+            }
+        }
+
+        impl ops::BitXorAssign for $ty {
+            fn bitxor_assign(&mut self, rhs: Self) {
+                *self = *self ^ rhs;
+            }
+        }
+
+        impl ops::Shl<u32> for $ty {
+            type Output = Self;
+
+            fn shl(self, rhs: u32) -> Self {
+                if rhs >= 128 {
+                    $ty {
+                        lo: 0,
+                        hi: self.lo << (rhs - 128),
+                    }
+                } else if rhs == 0 {
+                    self
+                } else {
+                    $ty {
+                        lo: self.lo << rhs,
+                        hi: self.hi << rhs | self.lo >> (128 - rhs),
+                    }
+                }
+            }
+        }
+
+        impl ops::ShlAssign<i32> for $ty {
+            fn shl_assign(&mut self, rhs: i32) {
+                *self = *self << rhs as u32;
+            }
+        }
+
+        impl ops::ShrAssign<u32> for $ty {
+            fn shr_assign(&mut self, rhs: u32) {
+                *self = *self >> rhs;
+            }
+        }
+    };
+}
+
+macro_rules! impl_int_common {
+    ($ty:ident) => {
+        const BITS: u32 = 256;
+
+        const ZERO: Self = $ty { lo: 0, hi: 0 };
+        const ONE: Self = $ty { lo: 1, hi: 0 };
+
+        // These are only used for fuzzing, and 256 bit integers are not fuzzed directly.
+        const FUZZ_LENGTHS: [u8; 20] = [
+            0, 1, 2, 7, 8, 15, 16, 63, 64, 127, 128, 191, 192, 239, 240, 247, 248, 253, 254, 255,
+        ];
+        const FUZZ_NUM: usize = 20;
+
+        fn from_bool(b: bool) -> Self {
+            $ty {
+                lo: b as u128,
+                hi: 0,
+            }
+        }
+
+        fn logical_shr(self, other: u32) -> Self {
+            Self::from_unsigned(self.unsigned().wrapping_shr(other))
+        }
+
+        fn is_zero(self) -> bool {
+            self == Self::ZERO
+        }
+
+        fn wrapping_neg(self) -> Self {
+            (!self).wrapping_add(Self::ONE)
+        }
+
+        fn wrapping_add(self, other: Self) -> Self {
+            let (lo, carry) = self.lo.overflowing_add(other.lo);
+            $ty {
+                lo,
+                hi: self.hi.wrapping_add(other.hi).wrapping_add(carry as u128),
+            }
+        }
+
+        fn wrapping_mul(self, other: Self) -> Self {
+            let lo = u128_widen_mul(self.lo, other.lo);
+            $ty {
+                lo: lo.lo,
+                hi: lo
+                    .hi
+                    .wrapping_add(self.lo.wrapping_mul(other.hi))
+                    .wrapping_add(self.hi.wrapping_mul(other.lo)),
+            }
+        }
+
+        fn wrapping_sub(self, other: Self) -> Self {
+            self.wrapping_add(other.wrapping_neg())
+        }
+
+        fn wrapping_shl(self, other: u32) -> Self {
+            self << (other & 255)
+        }
+
+        fn wrapping_shr(self, other: u32) -> Self {
+            self >> (other & 255)
+        }
+
+        fn rotate_left(self, other: u32) -> Self {
+            let other = other & 255;
+            if other == 0 {
+                self
+            } else {
+                Self::from_unsigned(self.unsigned() << other | self.unsigned() >> (256 - other))
+            }
+        }
+
+        fn leading_zeros(self) -> u32 {
+            if self.hi == 0 {
+                128 + self.lo.leading_zeros()
+            } else {
+                self.hi.leading_zeros()
+            }
+        }
+    };
+}
+
+impl_common!(u256);
+impl_common!(i256);
+
+impl ops::Shr<u32> for u256 {
+    type Output = Self;
+
+    fn shr(self, rhs: u32) -> Self {
+        if rhs >= 128 {
+            u256 {
+                lo: self.hi >> (rhs - 128),
+                hi: 0,
+            }
+        } else if rhs == 0 {
+            self
+        } else {
+            u256 {
+                lo: self.lo >> rhs | self.hi << (128 - rhs),
+                hi: self.hi >> rhs,
+            }
+        }
+    }
+}
+
+impl ops::Shr<u32> for i256 {
+    type Output = Self;
+
+    fn shr(self, rhs: u32) -> Self {
+        let hi = self.hi as i128;
+        if rhs >= 128 {
+            i256 {
+                lo: (hi >> (rhs - 128)) as u128,
+                hi: (hi >> 127) as u128,
+            }
+        } else if rhs == 0 {
+            self
+        } else {
+            i256 {
+                lo: self.lo >> rhs | self.hi << (128 - rhs),
+                hi: (hi >> rhs) as u128,
+            }
+        }
+    }
+}
+
+impl ops::Div for u256 {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        u256_div_rem(self, rhs).0
+    }
+}
+
+impl ops::Div for i256 {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        let quo = i256::from_unsigned(
+            u256_div_rem(self.abs_diff(i256::ZERO), rhs.abs_diff(i256::ZERO)).0,
+        );
+        if self.sign() != rhs.sign() {
+            quo.wrapping_neg()
+        } else {
+            quo
+        }
+    }
+}
+
+impl i256 {
+    fn sign(self) -> bool {
+        (self.hi as i128) < 0
+    }
+}
+
+impl Int for u256 {
+    type OtherSign = i256;
+    type UnsignedInt = u256;
+
+    const SIGNED: bool = false;
+    const MIN: Self = u256 { lo: 0, hi: 0 };
+    const MAX: Self = u256 {
+        lo: u128::MAX,
+        hi: u128::MAX,
+    };
+
+    fn unsigned(self) -> u256 {
+        self
+    }
+
+    fn from_unsigned(me: u256) -> Self {
+        me
+    }
+
+    fn abs_diff(self, other: Self) -> Self {
+        if self < other {
+            other.wrapping_sub(self)
+        } else {
+            self.wrapping_sub(other)
+        }
+    }
+
+    fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let sum = self.wrapping_add(other);
+        (sum, sum < self)
+    }
+
+    impl_int_common!(u256);
+}
+
+impl Int for i256 {
+    type OtherSign = u256;
+    type UnsignedInt = u256;
+
+    const SIGNED: bool = true;
+    const MIN: Self = i256 {
+        lo: 0,
+        hi: 1 << 127,
+    };
+    const MAX: Self = i256 {
+        lo: u128::MAX,
+        hi: u128::MAX >> 1,
+    };
+
+    fn unsigned(self) -> u256 {
+        u256 {
+            lo: self.lo,
+            hi: self.hi,
+        }
+    }
+
+    fn from_unsigned(me: u256) -> Self {
+        i256 {
+            lo: me.lo,
+            hi: me.hi,
+        }
+    }
+
+    fn abs_diff(self, other: Self) -> u256 {
+        let diff = self.wrapping_sub(other);
+        if diff.sign() {
+            diff.wrapping_neg().unsigned()
+        } else {
+            diff.unsigned()
+        }
+    }
+
+    fn overflowing_add(self, other: Self) -> (Self, bool) {
+        let sum = self.wrapping_add(other);
+        (
+            sum,
+            self.sign() == other.sign() && sum.sign() != self.sign(),
+        )
+    }
+
+    impl_int_common!(i256);
+}
+
+impl const DInt for u256 {
+    type H = u128;
+
+    fn lo(self) -> u128 {
+        self.lo
+    }
+    fn hi(self) -> u128 {
+        self.hi
+    }
+    fn lo_hi(self) -> (u128, u128) {
+        (self.lo, self.hi)
+    }
+    fn from_lo_hi(lo: u128, hi: u128) -> Self {
+        u256 { lo, hi }
+    }
+}
+
+impl const HInt for u128 {
+    type D = u256;
+
+    fn widen(self) -> u256 {
+        self.zero_widen()
+    }
+    fn zero_widen(self) -> u256 {
+        u256 { lo: self, hi: 0 }
+    }
+    fn widen_hi(self) -> u256 {
+        u256 { lo: 0, hi: self }
+    }
+    fn zero_widen_mul(self, rhs: Self) -> u256 {
+        u128_widen_mul(self, rhs)
+    }
+    fn widen_mul(self, rhs: Self) -> u256 {
+        u128_widen_mul(self, rhs)
+    }
+}
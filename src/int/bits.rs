@@ -0,0 +1,208 @@
+use int::{CastInto, Int};
+
+// The functions below are generic over the unsigned integers, so that the testcrate can check
+// them exhaustively at small widths. None of them use the primitive `trailing_zeros` or
+// `count_ones`, which LLVM may lower to calls of the very intrinsics defined here.
+
+public_test_dep! {
+/// Returns the number of trailing binary zeros in `x`, which is `I::BITS` for zero.
+pub(crate) fn trailing_zeros_default<I: Int + CastInto<u32>>(x: I) -> u32 {
+    // Bisect the number of trailing zeros like `usize_leading_zeros_default` does, shifting the
+    // higher part down whenever the lower part is all zeros.
+    let mut x = x;
+    let mut z = 0;
+    let mut s = I::BITS / 2;
+    while s != 0 {
+        if x & I::MAX.logical_shr(I::BITS - s) == I::ZERO {
+            x = x.logical_shr(s);
+            z += s;
+        }
+        s /= 2;
+    }
+    // Only the lowest bit is left to check. If it is zero as well, `x` was zero.
+    let lsb: u32 = (x & I::ONE).cast();
+    z + (lsb ^ 1)
+}
+}
+
+// Like `usize_leading_zeros_riscv`, this uses the set-if-less-than instruction of RISC-V to make
+// every bisection step branchless.
+
+public_test_dep! {
+/// Returns the number of trailing binary zeros in `x`, which is `I::BITS` for zero.
+pub(crate) fn trailing_zeros_riscv<I: Int + CastInto<u32>>(x: I) -> u32 {
+    let mut x = x;
+    let mut z = 0;
+    let mut s = I::BITS / 2;
+    while s != 0 {
+        // `t` is `s` if the lower `s` bits of `x` are all zero, and 0 otherwise. The comparison
+        // with one compiles to a single `sltiu`, and masking avoids a multiplication.
+        let low: I = x << (I::BITS - s);
+        let t = ((low < I::ONE) as u32).wrapping_neg() & s;
+        x = x.logical_shr(t);
+        z += t;
+        s /= 2;
+    }
+    let lsb: u32 = (x & I::ONE).cast();
+    z + (lsb ^ 1)
+}
+}
+
+public_test_dep! {
+/// Returns the number of ones in the binary representation of `x`.
+pub(crate) fn count_ones_default<I: Int + CastInto<u32>>(x: I) -> u32 {
+    let x = sum_bytes(x);
+    // Adds up all bytes in the highest byte
+    let ones = I::MAX / I::MAX.logical_shr(I::BITS - 8);
+    x.wrapping_mul(ones).logical_shr(I::BITS - 8).cast()
+}
+}
+
+// The multiplication above is a libcall on targets without a multiplier, so they add up the bytes
+// with shifts instead.
+
+public_test_dep! {
+/// Returns the number of ones in the binary representation of `x`.
+pub(crate) fn count_ones_no_mul<I: Int + CastInto<u32>>(x: I) -> u32 {
+    let mut x = sum_bytes(x);
+    let mut s = 8;
+    while s < I::BITS {
+        x += x.logical_shr(s);
+        s *= 2;
+    }
+    (x & I::MAX.logical_shr(I::BITS - 8)).cast()
+}
+}
+
+/// Returns the number of ones in every byte of `x`, in that byte
+fn sum_bytes<I: Int>(x: I) -> I {
+    // The masks are `0x55..`, `0x33..` and `0x0f..`
+    let m1 = I::MAX / (I::ONE + I::ONE + I::ONE);
+    let m2 = I::MAX / ((I::ONE << 2) + I::ONE);
+    let m4 = I::MAX / ((I::ONE << 4) + I::ONE);
+    let x = x - (x.logical_shr(1) & m1);
+    let x = (x & m2) + (x.logical_shr(2) & m2);
+    (x + x.logical_shr(4)) & m4
+}
+
+public_test_dep! {
+/// Returns 1 if the number of ones in the binary representation of `x` is odd, and 0 otherwise.
+pub(crate) fn parity<I: Int + CastInto<u32>>(x: I) -> u32 {
+    // Fold `x` down to four bits with the same parity, and look that up in a 16-bit table.
+    let mut x = x;
+    let mut s = I::BITS / 2;
+    while s >= 4 {
+        x ^= x.logical_shr(s);
+        s /= 2;
+    }
+    let nibble: u32 = (x & I::MAX.logical_shr(I::BITS - 4)).cast();
+    (0x6996 >> nibble) & 1
+}
+}
+
+fn trailing_zeros<I: Int + CastInto<u32>>(x: I) -> u32 {
+    if cfg!(any(target_arch = "riscv32", target_arch = "riscv64")) {
+        trailing_zeros_riscv(x)
+    } else {
+        trailing_zeros_default(x)
+    }
+}
+
+fn count_ones<I: Int + CastInto<u32>>(x: I) -> u32 {
+    if cfg!(any(
+        target_arch = "msp430",
+        all(
+            any(target_arch = "riscv32", target_arch = "riscv64"),
+            not(target_feature = "m")
+        )
+    )) {
+        count_ones_no_mul(x)
+    } else {
+        count_ones_default(x)
+    }
+}
+
+/// Returns one plus the index of the least significant one of `x`, or zero if `x` is zero.
+fn ffs<I: Int + CastInto<u32>>(x: I) -> i32 {
+    if x == I::ZERO {
+        0
+    } else {
+        trailing_zeros(x) as i32 + 1
+    }
+}
+
+intrinsics! {
+    pub extern "C" fn __popcountsi2(a: i32) -> i32 {
+        count_ones(a as u32) as i32
+    }
+
+    pub extern "C" fn __popcountdi2(a: i64) -> i32 {
+        count_ones(a as u64) as i32
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __popcountti2(a: i128) -> i32 {
+        (count_ones(a as u64) + count_ones((a >> 64) as u64)) as i32
+    }
+
+    pub extern "C" fn __paritysi2(a: i32) -> i32 {
+        parity(a as u32) as i32
+    }
+
+    pub extern "C" fn __paritydi2(a: i64) -> i32 {
+        parity(a as u64) as i32
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __parityti2(a: i128) -> i32 {
+        parity((a ^ (a >> 64)) as u64) as i32
+    }
+
+    /// Returns the number of trailing binary zeros in `a`, which must not be zero.
+    pub extern "C" fn __ctzsi2(a: i32) -> i32 {
+        trailing_zeros(a as u32) as i32
+    }
+
+    /// Returns the number of trailing binary zeros in `a`, which must not be zero.
+    pub extern "C" fn __ctzdi2(a: i64) -> i32 {
+        trailing_zeros(a as u64) as i32
+    }
+
+    #[unadjusted_on_win64]
+    /// Returns the number of trailing binary zeros in `a`, which must not be zero.
+    pub extern "C" fn __ctzti2(a: i128) -> i32 {
+        if a as u64 != 0 {
+            trailing_zeros(a as u64) as i32
+        } else {
+            trailing_zeros((a >> 64) as u64) as i32 + 64
+        }
+    }
+
+    pub extern "C" fn __ffssi2(a: i32) -> i32 {
+        ffs(a as u32)
+    }
+
+    pub extern "C" fn __ffsdi2(a: i64) -> i32 {
+        ffs(a as u64)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __ffsti2(a: i128) -> i32 {
+        if a as u64 != 0 {
+            ffs(a as u64)
+        } else {
+            match ffs((a >> 64) as u64) {
+                0 => 0,
+                i => i + 64,
+            }
+        }
+    }
+
+    pub extern "C" fn __bswapsi2(u: u32) -> u32 {
+        u.swap_bytes()
+    }
+
+    pub extern "C" fn __bswapdi2(u: u64) -> u64 {
+        u.swap_bytes()
+    }
+}
@@ -0,0 +1,55 @@
+use int::Int;
+
+/// Three-way comparison returning 0 if `a < b`, 1 if `a == b` and 2 if `a > b`, which is the
+/// result convention of the `__cmp*` and `__ucmp*` libcalls.
+fn cmp<I: Int>(a: I, b: I) -> i32 {
+    if a < b {
+        0
+    } else if a == b {
+        1
+    } else {
+        2
+    }
+}
+
+intrinsics! {
+    pub extern "C" fn __cmpdi2(a: i64, b: i64) -> i32 {
+        cmp(a, b)
+    }
+
+    pub extern "C" fn __ucmpdi2(a: u64, b: u64) -> i32 {
+        cmp(a, b)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __cmpti2(a: i128, b: i128) -> i32 {
+        cmp(a, b)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __ucmpti2(a: u128, b: u128) -> i32 {
+        cmp(a, b)
+    }
+
+    pub extern "C" fn __negdi2(a: i64) -> i64 {
+        a.wrapping_neg()
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __negti2(a: i128) -> i128 {
+        a.wrapping_neg()
+    }
+}
+
+// The run-time ABI variants return -1, 0 and 1 instead.
+
+#[cfg(target_arch = "arm")]
+intrinsics! {
+    pub extern "aapcs" fn __aeabi_lcmp(a: i64, b: i64) -> i32 {
+        cmp(a, b) - 1
+    }
+
+    pub extern "aapcs" fn __aeabi_ulcmp(a: u64, b: u64) -> i32 {
+        cmp(a, b) - 1
+    }
+}
@@ -3,42 +3,49 @@ use core::ops;
 mod specialized_div_rem;
 
 pub mod addsub;
+pub mod big;
+pub mod bits;
+pub mod cmp;
 pub mod leading_zeros;
 pub mod mul;
 pub mod sdiv;
 pub mod shift;
+pub mod trapv;
 pub mod udiv;
 
 pub use self::leading_zeros::__clzsi2;
+/// The unsigned `u128` division behind `__udivmodti4`, which can be used in const contexts
+pub use self::specialized_div_rem::u128_div_rem;
 
 public_test_dep! {
 /// Trait for some basic operations on integers
+#[const_trait]
 pub(crate) trait Int:
     Copy
     + core::fmt::Debug
-    + PartialEq
-    + PartialOrd
-    + ops::AddAssign
-    + ops::SubAssign
-    + ops::BitAndAssign
-    + ops::BitOrAssign
-    + ops::BitXorAssign
-    + ops::ShlAssign<i32>
-    + ops::ShrAssign<u32>
-    + ops::Add<Output = Self>
-    + ops::Sub<Output = Self>
-    + ops::Div<Output = Self>
-    + ops::Shl<u32, Output = Self>
-    + ops::Shr<u32, Output = Self>
-    + ops::BitOr<Output = Self>
-    + ops::BitXor<Output = Self>
-    + ops::BitAnd<Output = Self>
-    + ops::Not<Output = Self>
+    + ~const PartialEq
+    + ~const PartialOrd
+    + ~const ops::AddAssign
+    + ~const ops::SubAssign
+    + ~const ops::BitAndAssign
+    + ~const ops::BitOrAssign
+    + ~const ops::BitXorAssign
+    + ~const ops::ShlAssign<i32>
+    + ~const ops::ShrAssign<u32>
+    + ~const ops::Add<Output = Self>
+    + ~const ops::Sub<Output = Self>
+    + ~const ops::Div<Output = Self>
+    + ~const ops::Shl<u32, Output = Self>
+    + ~const ops::Shr<u32, Output = Self>
+    + ~const ops::BitOr<Output = Self>
+    + ~const ops::BitXor<Output = Self>
+    + ~const ops::BitAnd<Output = Self>
+    + ~const ops::Not<Output = Self>
 {
     /// Type with the same width but other signedness
-    type OtherSign: Int;
+    type OtherSign: ~const Int;
     /// Unsigned version of Self
-    type UnsignedInt: Int;
+    type UnsignedInt: ~const Int;
 
     /// If `Self` is a signed integer
     const SIGNED: bool;
@@ -203,7 +210,7 @@ macro_rules! int_impl_common {
 
 macro_rules! int_impl {
     ($ity:ty, $uty:ty) => {
-        impl Int for $uty {
+        impl const Int for $uty {
             type OtherSign = $ity;
             type UnsignedInt = $uty;
 
@@ -228,7 +235,7 @@ macro_rules! int_impl {
             int_impl_common!($uty);
         }
 
-        impl Int for $ity {
+        impl const Int for $ity {
             type OtherSign = $uty;
             type UnsignedInt = $uty;
 
@@ -259,9 +266,10 @@ int_impl!(i128, u128);
 public_test_dep! {
 /// Trait for integers twice the bit width of another integer. This is implemented for all
 /// primitives except for `u8`, because there is not a smaller primitive.
+#[const_trait]
 pub(crate) trait DInt: Int {
     /// Integer that is half the bit width of the integer this trait is implemented for
-    type H: HInt<D = Self> + Int;
+    type H: ~const HInt<D = Self> + Int;
 
     /// Returns the low half of `self`
     fn lo(self) -> Self::H;
@@ -277,9 +285,10 @@ pub(crate) trait DInt: Int {
 public_test_dep! {
 /// Trait for integers half the bit width of another integer. This is implemented for all
 /// primitives except for `u128`, because it there is not a larger primitive.
+#[const_trait]
 pub(crate) trait HInt: Int {
     /// Integer that is double the bit width of the integer this trait is implemented for
-    type D: DInt<H = Self> + Int;
+    type D: ~const DInt<H = Self> + Int;
 
     /// Widens (using default extension) the integer to have double bit width
     fn widen(self) -> Self::D;
@@ -298,7 +307,7 @@ pub(crate) trait HInt: Int {
 macro_rules! impl_d_int {
     ($($X:ident $D:ident),*) => {
         $(
-            impl DInt for $D {
+            impl const DInt for $D {
                 type H = $X;
 
                 fn lo(self) -> Self::H {
@@ -321,7 +330,7 @@ macro_rules! impl_d_int {
 macro_rules! impl_h_int {
     ($($H:ident $uH:ident $X:ident),*) => {
         $(
-            impl HInt for $H {
+            impl const HInt for $H {
                 type D = $X;
 
                 fn widen(self) -> Self::D {
@@ -358,6 +367,7 @@ impl_h_int!(
 
 public_test_dep! {
 /// Trait to express (possibly lossy) casting of integers
+#[const_trait]
 pub(crate) trait CastInto<T: Copy>: Copy {
     fn cast(self) -> T;
 }
@@ -368,7 +378,7 @@ macro_rules! cast_into {
         cast_into!($ty; usize, isize, u8, i8, u16, i16, u32, i32, u64, i64, u128, i128);
     };
     ($ty:ty; $($into:ty),*) => {$(
-        impl CastInto<$into> for $ty {
+        impl const CastInto<$into> for $ty {
             fn cast(self) -> $into {
                 self as $into
             }
@@ -27,9 +27,27 @@ where
     }
 }
 
+impl Mul for u32 {}
 impl Mul for u64 {}
 impl Mul for i128 {}
 
+/// Multiplies by shifting and adding. This is used for the narrowest multiplication, where a
+/// `Mul` built from half-width multiplications would call back into itself on targets such as
+/// MSP430 and AVR, which promote those to the same libcall.
+fn shift_add_mul<I: Int>(a: I, b: I) -> I {
+    let mut a = a;
+    let mut b = b;
+    let mut r = I::ZERO;
+    while a != I::ZERO {
+        if a & I::ONE != I::ZERO {
+            r = r.wrapping_add(b);
+        }
+        a = a.logical_shr(1);
+        b <<= 1;
+    }
+    r
+}
+
 pub(crate) trait UMulo: Int + DInt {
     fn mulo(self, rhs: Self) -> (Self, bool) {
         match (self.hi().is_zero(), rhs.hi().is_zero()) {
@@ -59,7 +77,7 @@ impl UMulo for u128 {}
 
 macro_rules! impl_signed_mulo {
     ($fn:ident, $iD:ident, $uD:ident) => {
-        fn $fn(lhs: $iD, rhs: $iD) -> ($iD, bool) {
+        pub(crate) fn $fn(lhs: $iD, rhs: $iD) -> ($iD, bool) {
             let mut lhs = lhs;
             let mut rhs = rhs;
             // the test against `mul_neg` below fails without this early return
@@ -98,6 +116,10 @@ impl_signed_mulo!(i64_overflowing_mul, i64, u64);
 impl_signed_mulo!(i128_overflowing_mul, i128, u128);
 
 intrinsics! {
+    pub extern "C" fn __mulhi3(a: i16, b: i16) -> i16 {
+        shift_add_mul(a as u16, b as u16) as i16
+    }
+
     #[maybe_use_optimized_c_shim]
     #[arm_aeabi_alias = __aeabi_lmul]
     pub extern "C" fn __muldi3(a: u64, b: u64) -> u64 {
@@ -135,3 +157,20 @@ intrinsics! {
         a.mulo(b)
     }
 }
+
+// The MSP430 EABI names of the multiplications, which LLVM calls on targets without a hardware
+// multiplier
+#[cfg(target_arch = "msp430")]
+intrinsics! {
+    pub extern "C" fn __mspabi_mpyi(a: i16, b: i16) -> i16 {
+        __mulhi3(a, b)
+    }
+
+    pub extern "C" fn __mspabi_mpyl(a: u32, b: u32) -> u32 {
+        a.mul(b)
+    }
+
+    pub extern "C" fn __mspabi_mpyll(a: u64, b: u64) -> u64 {
+        __muldi3(a, b)
+    }
+}
@@ -111,6 +111,14 @@ macro_rules! smod {
     }
 }
 
+// On AVR, LLVM calls `__divmodhi4` and `__udivmodhi4` with the convention of avr-libgcc, which
+// returns the quotient and the remainder together in registers instead of through `rem`. These
+// have the C signature, so they are left to avr-libgcc there.
+#[cfg(not(target_arch = "avr"))]
+sdivmod!(__udivmodhi4, __divmodhi4, u16, i16,);
+sdiv!(__udivhi3, __divhi3, u16, i16,);
+smod!(__umodhi3, __modhi3, u16, i16,);
+
 sdivmod!(
     __udivmodsi4,
     __divmodsi4,
@@ -164,3 +172,31 @@ sdivmod!(
 );
 sdiv!(__udivti3, __divti3, u128, i128, win64_128bit_abi_hack);
 smod!(__umodti3, __modti3, u128, i128, win64_128bit_abi_hack);
+
+// The MSP430 EABI names of the signed divisions, which LLVM calls instead of the ones above
+#[cfg(target_arch = "msp430")]
+intrinsics! {
+    pub extern "C" fn __mspabi_divi(a: i16, b: i16) -> i16 {
+        __divhi3(a, b)
+    }
+
+    pub extern "C" fn __mspabi_remi(a: i16, b: i16) -> i16 {
+        __modhi3(a, b)
+    }
+
+    pub extern "C" fn __mspabi_divli(a: i32, b: i32) -> i32 {
+        __divsi3(a, b)
+    }
+
+    pub extern "C" fn __mspabi_remli(a: i32, b: i32) -> i32 {
+        __modsi3(a, b)
+    }
+
+    pub extern "C" fn __mspabi_divlli(a: i64, b: i64) -> i64 {
+        __divdi3(a, b)
+    }
+
+    pub extern "C" fn __mspabi_remlli(a: i64, b: i64) -> i64 {
+        __moddi3(a, b)
+    }
+}
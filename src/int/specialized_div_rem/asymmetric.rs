@@ -17,7 +17,7 @@ macro_rules! impl_asymmetric {
     ) => {
         /// Computes the quotient and remainder of `duo` divided by `div` and returns them as a
         /// tuple.
-        pub fn $fn(duo: $uD, div: $uD) -> ($uD, $uD) {
+        pub const fn $fn(duo: $uD, div: $uD) -> ($uD, $uD) {
             let n: u32 = $n_h * 2;
 
             let duo_lo = duo as $uX;
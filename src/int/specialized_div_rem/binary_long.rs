@@ -16,7 +16,7 @@ macro_rules! impl_binary_long {
     ) => {
         /// Computes the quotient and remainder of `duo` divided by `div` and returns them as a
         /// tuple.
-        pub fn $fn(duo: $uX, div: $uX) -> ($uX, $uX) {
+        pub const fn $fn(duo: $uX, div: $uX) -> ($uX, $uX) {
             let mut duo = duo;
             // handle edge cases before calling `$normalization_shift`
             if div == 0 {
@@ -71,7 +71,7 @@ mod asymmetric;
 /// The behavior of all divisions by zero is controlled by this function. This function should be
 /// impossible to reach by Rust users, unless `compiler-builtins` public division functions or
 /// `core/std::unchecked_div/rem` are directly used without a zero check in front.
-fn zero_div_fn() -> ! {
+const fn zero_div_fn() -> ! {
     unsafe { core::hint::unreachable_unchecked() }
 }
 
@@ -108,6 +108,14 @@ impl_normalization_shift!(
     i32,
     allow(dead_code)
 );
+impl_normalization_shift!(
+    u16_normalization_shift,
+    USE_LZ,
+    16,
+    u16,
+    i16,
+    allow(dead_code)
+);
 impl_normalization_shift!(
     u64_normalization_shift,
     USE_LZ,
@@ -121,7 +129,7 @@ impl_normalization_shift!(
 /// `checked_div` and `checked_rem` are used to avoid bringing in panic function
 /// dependencies.
 #[inline]
-fn u64_by_u64_div_rem(duo: u64, div: u64) -> (u64, u64) {
+const fn u64_by_u64_div_rem(duo: u64, div: u64) -> (u64, u64) {
     if let Some(quo) = duo.checked_div(div) {
         if let Some(rem) = duo.checked_rem(div) {
             return (quo, rem);
@@ -175,7 +183,20 @@ impl_delegate!(
 /// If `div == 0`, then a division by zero exception occurs.
 #[cfg(all(not(feature = "no-asm"), target_arch = "x86_64"))]
 #[inline]
-unsafe fn u128_by_u64_div_rem(duo: u128, div: u64) -> (u64, u64) {
+const unsafe fn u128_by_u64_div_rem(duo: u128, div: u64) -> (u64, u64) {
+    // The `div` instruction is not available during constant evaluation, which has to use the
+    // plain division of `u128` instead.
+    const fn const_div_rem(duo: u128, div: u64) -> (u64, u64) {
+        ((duo / div as u128) as u64, (duo % div as u128) as u64)
+    }
+    unsafe {
+        core::intrinsics::const_eval_select((duo, div), const_div_rem, u128_by_u64_div_rem_asm)
+    }
+}
+
+#[cfg(all(not(feature = "no-asm"), target_arch = "x86_64"))]
+#[inline]
+fn u128_by_u64_div_rem_asm(duo: u128, div: u64) -> (u64, u64) {
     let duo_lo = duo as u64;
     let duo_hi = (duo >> 64) as u64;
     let quo: u64;
@@ -213,7 +234,7 @@ impl_asymmetric!(
 /// dependencies.
 #[inline]
 #[allow(dead_code)]
-fn u32_by_u32_div_rem(duo: u32, div: u32) -> (u32, u32) {
+const fn u32_by_u32_div_rem(duo: u32, div: u32) -> (u32, u32) {
     if let Some(quo) = duo.checked_div(div) {
         if let Some(rem) = duo.checked_rem(div) {
             return (quo, rem);
@@ -262,7 +283,20 @@ impl_binary_long!(
 /// If `div == 0`, then a division by zero exception occurs.
 #[cfg(all(not(feature = "no-asm"), target_arch = "x86"))]
 #[inline]
-unsafe fn u64_by_u32_div_rem(duo: u64, div: u32) -> (u32, u32) {
+const unsafe fn u64_by_u32_div_rem(duo: u64, div: u32) -> (u32, u32) {
+    // The `div` instruction is not available during constant evaluation, which has to use the
+    // plain division of `u64` instead.
+    const fn const_div_rem(duo: u64, div: u32) -> (u32, u32) {
+        ((duo / div as u64) as u32, (duo % div as u64) as u32)
+    }
+    unsafe {
+        core::intrinsics::const_eval_select((duo, div), const_div_rem, u64_by_u32_div_rem_asm)
+    }
+}
+
+#[cfg(all(not(feature = "no-asm"), target_arch = "x86"))]
+#[inline]
+fn u64_by_u32_div_rem_asm(duo: u64, div: u32) -> (u32, u32) {
     let duo_lo = duo as u32;
     let duo_hi = (duo >> 32) as u32;
     let quo: u32;
@@ -295,7 +329,6 @@ impl_asymmetric!(
     u64
 );
 
-// 32 bits is the smallest division used by `compiler-builtins`, so we end with binary long division
 impl_binary_long!(
     u32_div_rem,
     zero_div_fn,
@@ -304,3 +337,14 @@ impl_binary_long!(
     u32,
     i32
 );
+
+// 16 bits is the smallest division used by `compiler-builtins`, which is only needed by 16-bit
+// targets such as MSP430 and AVR. Binary long division is used for it as well.
+impl_binary_long!(
+    u16_div_rem,
+    zero_div_fn,
+    u16_normalization_shift,
+    16,
+    u16,
+    i16
+);
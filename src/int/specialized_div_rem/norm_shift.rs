@@ -46,7 +46,7 @@ macro_rules! impl_normalization_shift {
         $(
             #[$unsigned_attr]
         )*
-        fn $name(duo: $uX, div: $uX, full_normalization: bool) -> usize {
+        const fn $name(duo: $uX, div: $uX, full_normalization: bool) -> usize {
             // We have to find the leading zeros of `div` to know where its msb (most significant
             // set bit) is to even begin binary long division. It is also good to know where the msb
             // of `duo` is so that useful work can be started instead of shifting `div` for all
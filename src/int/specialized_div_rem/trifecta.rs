@@ -15,18 +15,18 @@ macro_rules! impl_trifecta {
     ) => {
         /// Computes the quotient and remainder of `duo` divided by `div` and returns them as a
         /// tuple.
-        pub fn $fn(duo: $uD, div: $uD) -> ($uD, $uD) {
+        pub const fn $fn(duo: $uD, div: $uD) -> ($uD, $uD) {
             // This is called the trifecta algorithm because it uses three main algorithms: short
             // division for small divisors, the two possibility algorithm for large divisors, and an
             // undersubtracting long division algorithm for intermediate cases.
 
             // This replicates `carrying_mul` (rust-lang rfc #2417). LLVM correctly optimizes this
             // to use a widening multiply to 128 bits on the relevant architectures.
-            fn carrying_mul(lhs: $uX, rhs: $uX) -> ($uX, $uX) {
+            const fn carrying_mul(lhs: $uX, rhs: $uX) -> ($uX, $uX) {
                 let tmp = (lhs as $uD).wrapping_mul(rhs as $uD);
                 (tmp as $uX, (tmp >> ($n_h * 2)) as $uX)
             }
-            fn carrying_mul_add(lhs: $uX, mul: $uX, add: $uX) -> ($uX, $uX) {
+            const fn carrying_mul_add(lhs: $uX, mul: $uX, add: $uX) -> ($uX, $uX) {
                 let tmp = (lhs as $uD)
                     .wrapping_mul(mul as $uD)
                     .wrapping_add(add as $uD);
@@ -0,0 +1,139 @@
+use int::addsub::{Addo, Subo};
+use int::mul::{i128_overflowing_mul, i32_overflowing_mul, i64_overflowing_mul};
+
+// The checked arithmetic that C code compiled with `-ftrapv` calls into. All of these abort
+// through `__compilerrt_abort_impl` on overflow, which has weak linkage just like in compiler-rt,
+// so that applications can install their own trap handler by defining a strong symbol.
+
+/// Aborts the program. `file` and `function` are NUL-terminated strings describing where the
+/// overflow was detected.
+#[cfg_attr(not(feature = "mangled-names"), no_mangle)]
+#[cfg_attr(not(target_os = "windows"), linkage = "weak")]
+pub extern "C" fn __compilerrt_abort_impl(_file: *const u8, _line: i32, _function: *const u8) -> ! {
+    unsafe { core::intrinsics::abort() }
+}
+
+macro_rules! trap {
+    ($name:expr) => {
+        __compilerrt_abort_impl(
+            concat!(file!(), "\0").as_ptr(),
+            line!() as i32,
+            concat!($name, "\0").as_ptr(),
+        )
+    };
+}
+
+intrinsics! {
+    pub extern "C" fn __addvsi3(a: i32, b: i32) -> i32 {
+        match a.overflowing_add(b) {
+            (s, false) => s,
+            (_, true) => trap!("__addvsi3"),
+        }
+    }
+
+    pub extern "C" fn __addvdi3(a: i64, b: i64) -> i64 {
+        match a.overflowing_add(b) {
+            (s, false) => s,
+            (_, true) => trap!("__addvdi3"),
+        }
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __addvti3(a: i128, b: i128) -> i128 {
+        match a.addo(b) {
+            (s, false) => s,
+            (_, true) => trap!("__addvti3"),
+        }
+    }
+
+    pub extern "C" fn __subvsi3(a: i32, b: i32) -> i32 {
+        match a.overflowing_sub(b) {
+            (s, false) => s,
+            (_, true) => trap!("__subvsi3"),
+        }
+    }
+
+    pub extern "C" fn __subvdi3(a: i64, b: i64) -> i64 {
+        match a.overflowing_sub(b) {
+            (s, false) => s,
+            (_, true) => trap!("__subvdi3"),
+        }
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __subvti3(a: i128, b: i128) -> i128 {
+        match a.subo(b) {
+            (s, false) => s,
+            (_, true) => trap!("__subvti3"),
+        }
+    }
+
+    pub extern "C" fn __mulvsi3(a: i32, b: i32) -> i32 {
+        match i32_overflowing_mul(a, b) {
+            (m, false) => m,
+            (_, true) => trap!("__mulvsi3"),
+        }
+    }
+
+    pub extern "C" fn __mulvdi3(a: i64, b: i64) -> i64 {
+        match i64_overflowing_mul(a, b) {
+            (m, false) => m,
+            (_, true) => trap!("__mulvdi3"),
+        }
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __mulvti3(a: i128, b: i128) -> i128 {
+        match i128_overflowing_mul(a, b) {
+            (m, false) => m,
+            (_, true) => trap!("__mulvti3"),
+        }
+    }
+
+    pub extern "C" fn __negvsi2(a: i32) -> i32 {
+        if a == i32::MIN {
+            trap!("__negvsi2")
+        }
+        a.wrapping_neg()
+    }
+
+    pub extern "C" fn __negvdi2(a: i64) -> i64 {
+        if a == i64::MIN {
+            trap!("__negvdi2")
+        }
+        a.wrapping_neg()
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __negvti2(a: i128) -> i128 {
+        if a == i128::MIN {
+            trap!("__negvti2")
+        }
+        a.wrapping_neg()
+    }
+
+    pub extern "C" fn __absvsi2(a: i32) -> i32 {
+        if a == i32::MIN {
+            trap!("__absvsi2")
+        }
+        let t = a >> 31;
+        (a ^ t).wrapping_sub(t)
+    }
+
+    pub extern "C" fn __absvdi2(a: i64) -> i64 {
+        if a == i64::MIN {
+            trap!("__absvdi2")
+        }
+        let t = a >> 63;
+        (a ^ t).wrapping_sub(t)
+    }
+
+    #[unadjusted_on_win64]
+    pub extern "C" fn __absvti2(a: i128) -> i128 {
+        if a == i128::MIN {
+            trap!("__absvti2")
+        }
+        let t = a >> 127;
+        (a ^ t).wrapping_sub(t)
+    }
+}
@@ -5,6 +5,27 @@ pub(crate) use int::specialized_div_rem::*;
 pub use int::specialized_div_rem::*;
 
 intrinsics! {
+    /// Returns `n / d`
+    pub extern "C" fn __udivhi3(n: u16, d: u16) -> u16 {
+        u16_div_rem(n, d).0
+    }
+
+    /// Returns `n % d`
+    pub extern "C" fn __umodhi3(n: u16, d: u16) -> u16 {
+        u16_div_rem(n, d).1
+    }
+
+    // See `__divmodhi4` for why AVR is left out.
+    #[cfg(not(target_arch = "avr"))]
+    /// Returns `n / d` and sets `*rem = n % d`
+    pub extern "C" fn __udivmodhi4(n: u16, d: u16, rem: Option<&mut u16>) -> u16 {
+        let quo_rem = u16_div_rem(n, d);
+        if let Some(rem) = rem {
+            *rem = quo_rem.1;
+        }
+        quo_rem.0
+    }
+
     #[maybe_use_optimized_c_shim]
     #[arm_aeabi_alias = __aeabi_uidiv]
     /// Returns `n / d`
@@ -97,3 +118,31 @@ intrinsics! {
         }
     }
 }
+
+// The MSP430 EABI names of the unsigned divisions, which LLVM calls instead of the ones above
+#[cfg(target_arch = "msp430")]
+intrinsics! {
+    pub extern "C" fn __mspabi_divu(n: u16, d: u16) -> u16 {
+        __udivhi3(n, d)
+    }
+
+    pub extern "C" fn __mspabi_remu(n: u16, d: u16) -> u16 {
+        __umodhi3(n, d)
+    }
+
+    pub extern "C" fn __mspabi_divul(n: u32, d: u32) -> u32 {
+        __udivsi3(n, d)
+    }
+
+    pub extern "C" fn __mspabi_remul(n: u32, d: u32) -> u32 {
+        __umodsi3(n, d)
+    }
+
+    pub extern "C" fn __mspabi_divull(n: u64, d: u64) -> u64 {
+        __udivdi3(n, d)
+    }
+
+    pub extern "C" fn __mspabi_remull(n: u64, d: u64) -> u64 {
+        __umoddi3(n, d)
+    }
+}
@@ -4,7 +4,13 @@
 #![cfg_attr(not(feature = "no-asm"), feature(global_asm))]
 #![feature(cfg_target_has_atomic)]
 #![feature(compiler_builtins)]
+#![feature(const_eval_select)]
+#![feature(const_fn_trait_bound)]
+#![feature(const_trait_impl)]
+#![feature(const_ops)]
+#![feature(const_cmp)]
 #![feature(core_intrinsics)]
+#![feature(integer_atomics)]
 #![feature(lang_items)]
 #![feature(linkage)]
 #![feature(naked_functions)]
@@ -35,6 +41,9 @@ extern crate core;
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "atomic")]
+pub mod atomic;
+pub mod clear_cache;
 pub mod float;
 pub mod int;
 
@@ -46,6 +55,14 @@ pub mod int;
 pub mod math;
 pub mod mem;
 
+// `build.rs` only builds the outline atomics of compiler-rt where this module is left out
+#[cfg(all(
+    target_arch = "aarch64",
+    not(any(target_vendor = "apple", target_os = "windows")),
+    not(feature = "no-asm")
+))]
+pub mod aarch64;
+
 #[cfg(target_arch = "arm")]
 pub mod arm;
 
@@ -59,8 +59,6 @@ no_mangle! {
     fn exp2f(x: f32) -> f32;
     fn fmod(x: f64, y: f64) -> f64;
     fn fmodf(x: f32, y: f32) -> f32;
-    fn fma(x: f64, y: f64, z: f64) -> f64;
-    fn fmaf(x: f32, y: f32, z: f32) -> f32;
     fn acosf(n: f32) -> f32;
     fn asinf(n: f32) -> f32;
     fn atan2f(a: f32, b: f32) -> f32;
@@ -1,4 +1,5 @@
 #![allow(unused_macros)]
+#![feature(simd_ffi)]
 
 use testcrate::*;
 
@@ -115,7 +116,7 @@ fn float_addsub() {
 fn float_addsub_arm() {
     use compiler_builtins::float::{
         add::{__adddf3vfp, __addsf3vfp},
-        sub::{__subdf3vfp, __subsf3vfp},
+        sub::{__negdf2vfp, __negsf2vfp, __subdf3vfp, __subsf3vfp},
         Float,
     };
 
@@ -123,4 +124,53 @@ fn float_addsub_arm() {
         f32, __addsf3vfp, __subsf3vfp;
         f64, __adddf3vfp, __subdf3vfp;
     );
+    fuzz_float(N, |x: f32| {
+        assert_eq!(__negsf2vfp(x).to_bits(), (-x).to_bits());
+    });
+    fuzz_float(N, |x: f64| {
+        assert_eq!(__negdf2vfp(x).to_bits(), (-x).to_bits());
+    });
+}
+
+// There is no native `f128` to compare against, so the libgcc implementations are used as the
+// reference.
+#[cfg(all(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "mips64"
+    ),
+    target_os = "linux"
+))]
+#[test]
+fn float_addsub_f128() {
+    use compiler_builtins::float::{add::__addtf3, f128, sub::__subtf3, Float};
+
+    // `f128` is passed like `__float128`, even though the lint cannot tell.
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "__addtf3"]
+        fn gcc_addtf3(a: f128, b: f128) -> f128;
+        #[link_name = "__subtf3"]
+        fn gcc_subtf3(a: f128, b: f128) -> f128;
+    }
+
+    fuzz_float_2(N, |x: f128, y: f128| {
+        let add0 = unsafe { gcc_addtf3(x, y) };
+        let sub0 = unsafe { gcc_subtf3(x, y) };
+        let add1 = __addtf3(x, y);
+        let sub1 = __subtf3(x, y);
+        if !Float::eq_repr(add0, add1) {
+            panic!(
+                "__addtf3({:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                x, y, add0, add1
+            );
+        }
+        if !Float::eq_repr(sub0, sub1) {
+            panic!(
+                "__subtf3({:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                x, y, sub0, sub1
+            );
+        }
+    });
 }
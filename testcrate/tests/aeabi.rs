@@ -0,0 +1,193 @@
+// The helpers of the ARM run-time ABI that have to be provided without the C implementations.
+// Naming every one of them makes this test fail to build when one is missing, which covers the
+// unmangled symbols as well, because both are generated together.
+#![cfg(all(target_arch = "arm", not(any(target_os = "ios", target_env = "msvc"))))]
+
+use compiler_builtins::float::Float;
+use testcrate::*;
+
+macro_rules! symbols {
+    ($($path:path),* $(,)?) => {
+        [$((stringify!($path), $path as usize)),*]
+    };
+}
+
+#[test]
+fn aeabi_symbols() {
+    use compiler_builtins::float::{add, cmp, conv, div, extend, mul, sub, trunc};
+    use compiler_builtins::int::{cmp as icmp, mul as imul, sdiv, shift, udiv};
+
+    let symbols = symbols![
+        add::__aeabi_dadd::__aeabi_dadd,
+        div::__aeabi_ddiv::__aeabi_ddiv,
+        mul::__aeabi_dmul::__aeabi_dmul,
+        sub::__aeabi_drsub,
+        sub::__aeabi_dsub::__aeabi_dsub,
+        cmp::__aeabi_dcmpeq,
+        cmp::__aeabi_dcmplt,
+        cmp::__aeabi_dcmple,
+        cmp::__aeabi_dcmpge,
+        cmp::__aeabi_dcmpgt,
+        cmp::__aeabi_dcmpun::__aeabi_dcmpun,
+        add::__aeabi_fadd::__aeabi_fadd,
+        div::__aeabi_fdiv::__aeabi_fdiv,
+        mul::__aeabi_fmul::__aeabi_fmul,
+        sub::__aeabi_frsub,
+        sub::__aeabi_fsub::__aeabi_fsub,
+        cmp::__aeabi_fcmpeq,
+        cmp::__aeabi_fcmplt,
+        cmp::__aeabi_fcmple,
+        cmp::__aeabi_fcmpge,
+        cmp::__aeabi_fcmpgt,
+        cmp::__aeabi_fcmpun::__aeabi_fcmpun,
+        conv::__aeabi_d2iz::__aeabi_d2iz,
+        conv::__aeabi_d2uiz::__aeabi_d2uiz,
+        conv::__aeabi_d2lz::__aeabi_d2lz,
+        conv::__aeabi_d2ulz::__aeabi_d2ulz,
+        conv::__aeabi_f2iz::__aeabi_f2iz,
+        conv::__aeabi_f2uiz::__aeabi_f2uiz,
+        conv::__aeabi_f2lz::__aeabi_f2lz,
+        conv::__aeabi_f2ulz::__aeabi_f2ulz,
+        trunc::__aeabi_d2f::__aeabi_d2f,
+        trunc::__aeabi_d2h::__aeabi_d2h,
+        extend::__aeabi_f2d::__aeabi_f2d,
+        trunc::__aeabi_f2h::__aeabi_f2h,
+        extend::__aeabi_h2f::__aeabi_h2f,
+        conv::__aeabi_i2d::__aeabi_i2d,
+        conv::__aeabi_ui2d::__aeabi_ui2d,
+        conv::__aeabi_l2d::__aeabi_l2d,
+        conv::__aeabi_ul2d::__aeabi_ul2d,
+        conv::__aeabi_i2f::__aeabi_i2f,
+        conv::__aeabi_ui2f::__aeabi_ui2f,
+        conv::__aeabi_l2f::__aeabi_l2f,
+        conv::__aeabi_ul2f::__aeabi_ul2f,
+        imul::__aeabi_lmul::__aeabi_lmul,
+        shift::__aeabi_llsl::__aeabi_llsl,
+        shift::__aeabi_llsr::__aeabi_llsr,
+        shift::__aeabi_lasr::__aeabi_lasr,
+        icmp::__aeabi_lcmp,
+        icmp::__aeabi_ulcmp,
+        sdiv::__aeabi_idiv::__aeabi_idiv,
+        udiv::__aeabi_uidiv::__aeabi_uidiv,
+    ];
+    for (name, address) in symbols.iter() {
+        assert_ne!(*address, 0, "{}", name);
+    }
+}
+
+/// The helpers that are written with `asm!`
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn aeabi_asm_symbols() {
+    use compiler_builtins::arm;
+
+    let symbols = symbols![
+        arm::__aeabi_cdcmpeq,
+        arm::__aeabi_cdcmple,
+        arm::__aeabi_cdrcmple,
+        arm::__aeabi_cfcmpeq,
+        arm::__aeabi_cfcmple,
+        arm::__aeabi_cfrcmple,
+        arm::__aeabi_idivmod,
+        arm::__aeabi_uidivmod,
+        arm::__aeabi_ldivmod,
+        arm::__aeabi_uldivmod,
+        arm::__aeabi_idiv0,
+        arm::__aeabi_ldiv0,
+        arm::__aeabi_uread4,
+        arm::__aeabi_uread8,
+        arm::__aeabi_uwrite4,
+        arm::__aeabi_uwrite8,
+        arm::__aeabi_memcpy,
+        arm::__aeabi_memcpy4,
+        arm::__aeabi_memcpy8,
+        arm::__aeabi_memmove,
+        arm::__aeabi_memmove4,
+        arm::__aeabi_memmove8,
+        arm::__aeabi_memset,
+        arm::__aeabi_memset4,
+        arm::__aeabi_memset8,
+        arm::__aeabi_memclr,
+        arm::__aeabi_memclr4,
+        arm::__aeabi_memclr8,
+    ];
+    for (name, address) in symbols.iter() {
+        assert_ne!(*address, 0, "{}", name);
+    }
+}
+
+#[test]
+fn aeabi_lmul_shift() {
+    use compiler_builtins::int::mul::__aeabi_lmul::__aeabi_lmul;
+    use compiler_builtins::int::shift::{
+        __aeabi_lasr::__aeabi_lasr, __aeabi_llsl::__aeabi_llsl, __aeabi_llsr::__aeabi_llsr,
+    };
+
+    fuzz_2(N, |x: u64, y: u64| {
+        assert_eq!(
+            __aeabi_lmul(x, y),
+            x.wrapping_mul(y),
+            "__aeabi_lmul({}, {})",
+            x,
+            y
+        );
+    });
+    fuzz_shift(|x: u64, s: u32| {
+        assert_eq!(__aeabi_llsl(x, s), x << s, "__aeabi_llsl({}, {})", x, s);
+        assert_eq!(__aeabi_llsr(x, s), x >> s, "__aeabi_llsr({}, {})", x, s);
+    });
+    fuzz_shift(|x: i64, s: u32| {
+        assert_eq!(__aeabi_lasr(x, s), x >> s, "__aeabi_lasr({}, {})", x, s);
+    });
+}
+
+#[test]
+fn aeabi_rsub() {
+    use compiler_builtins::float::sub::{__aeabi_drsub, __aeabi_frsub};
+
+    fuzz_float_2(N, |x: f32, y: f32| {
+        let rsub0 = y - x;
+        let rsub1 = __aeabi_frsub(x, y);
+        if !Float::eq_repr(rsub0, rsub1) {
+            panic!(
+                "__aeabi_frsub({}, {}): std: {}, builtins: {}",
+                x, y, rsub0, rsub1
+            );
+        }
+    });
+    fuzz_float_2(N, |x: f64, y: f64| {
+        let rsub0 = y - x;
+        let rsub1 = __aeabi_drsub(x, y);
+        if !Float::eq_repr(rsub0, rsub1) {
+            panic!(
+                "__aeabi_drsub({}, {}): std: {}, builtins: {}",
+                x, y, rsub0, rsub1
+            );
+        }
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn aeabi_div0_unaligned() {
+    use compiler_builtins::arm::{
+        __aeabi_idiv0, __aeabi_ldiv0, __aeabi_uread4, __aeabi_uread8, __aeabi_uwrite4,
+        __aeabi_uwrite8,
+    };
+
+    // The division by zero handlers return what they are given, unless they are replaced.
+    assert_eq!(__aeabi_idiv0(-7), -7);
+    assert_eq!(__aeabi_ldiv0(i64::MIN), i64::MIN);
+
+    fuzz_2(N, |x: i64, y: i64| unsafe {
+        let mut buf = [0u8; 16];
+        for offset in 0..4 {
+            let p = buf.as_mut_ptr().add(offset);
+            assert_eq!(__aeabi_uwrite8(x, p as *mut i64), x);
+            assert_eq!(__aeabi_uread8(p as *const i64), x);
+            assert_eq!(__aeabi_uwrite4(y as i32, p.add(8) as *mut i32), y as i32);
+            assert_eq!(__aeabi_uread4(p.add(8) as *const i32), y as i32);
+            assert_eq!(__aeabi_uread8(p as *const i64), x);
+        }
+    });
+}
@@ -0,0 +1,104 @@
+// The `__sync_*` functions are only built for the ARM targets that predate the atomic
+// instructions, and rely on the kernel user helpers which `qemu-arm` also provides.
+#![cfg(all(target_arch = "arm", target_os = "linux", not(target_feature = "v6")))]
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use compiler_builtins::arm_linux::*;
+use testcrate::*;
+
+macro_rules! sync_8 {
+    ($($ty:ty, $fn:ident, $op:expr;)*) => {
+        $(
+            fuzz_2(N, |x: $ty, y: $ty| unsafe {
+                let mut v = x;
+                let old = $fn(&mut v, y);
+                let expected: $ty = $op(x, y);
+                if old != x || v != expected {
+                    panic!(
+                        "{}({}, {}): expected ({}, {}), found ({}, {})",
+                        stringify!($fn), x, y, x, expected, old, v
+                    );
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn sync_8() {
+    sync_8!(
+        u64, __sync_fetch_and_add_8, |a: u64, b: u64| a.wrapping_add(b);
+        u64, __sync_fetch_and_sub_8, |a: u64, b: u64| a.wrapping_sub(b);
+        u64, __sync_fetch_and_and_8, |a: u64, b: u64| a & b;
+        u64, __sync_fetch_and_or_8, |a: u64, b: u64| a | b;
+        u64, __sync_fetch_and_xor_8, |a: u64, b: u64| a ^ b;
+        u64, __sync_fetch_and_nand_8, |a: u64, b: u64| !(a & b);
+        i64, __sync_fetch_and_max_8, |a: i64, b: i64| a.max(b);
+        u64, __sync_fetch_and_umax_8, |a: u64, b: u64| a.max(b);
+        i64, __sync_fetch_and_min_8, |a: i64, b: i64| a.min(b);
+        u64, __sync_fetch_and_umin_8, |a: u64, b: u64| a.min(b);
+        u64, __sync_lock_test_and_set_8, |_: u64, b: u64| b;
+    );
+}
+
+#[test]
+fn sync_val_compare_and_swap_8() {
+    fuzz_2(N, |x: u64, y: u64| unsafe {
+        let mut v = x;
+        assert_eq!(__sync_val_compare_and_swap_8(&mut v, x, y), x);
+        assert_eq!(v, y);
+        if x != y {
+            assert_eq!(__sync_val_compare_and_swap_8(&mut v, x, !y), y);
+            assert_eq!(v, y);
+        }
+    });
+}
+
+/// A `u64` shared between threads, which is only accessed through the `__sync_*` functions
+struct Shared(*mut u64);
+
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+#[test]
+fn sync_contention_8() {
+    const THREADS: usize = 4;
+    const ITERATIONS: u64 = 10_000;
+
+    let shared = Arc::new(Shared(Box::into_raw(Box::new(0u64))));
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let shared = shared.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || unsafe {
+                barrier.wait();
+                for i in 0..ITERATIONS {
+                    // Carries across the halves of the value, so torn updates would be visible
+                    if i % 2 == 0 {
+                        __sync_fetch_and_add_8(shared.0, 1 << 32 | 1);
+                    } else {
+                        let mut old = __sync_fetch_and_add_8(shared.0, 0);
+                        loop {
+                            let new = old.wrapping_add(1 << 32 | 1);
+                            match __sync_val_compare_and_swap_8(shared.0, old, new) {
+                                cur if cur == old => break,
+                                cur => old = cur,
+                            }
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let total = THREADS as u64 * ITERATIONS;
+    unsafe {
+        assert_eq!(__sync_fetch_and_add_8(shared.0, 0), total << 32 | total);
+        drop(Box::from_raw(shared.0));
+    }
+}
@@ -0,0 +1,227 @@
+#![cfg(feature = "atomic")]
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use compiler_builtins::atomic::*;
+use testcrate::*;
+
+macro_rules! sized {
+    ($($ty:ident, $load:ident, $store:ident, $exchange:ident, $compare_exchange:ident,
+        $add:ident, $sub:ident, $and:ident, $or:ident, $xor:ident, $nand:ident;)*) => {
+        $(
+            fuzz_2(N, |x: $ty, y: $ty| unsafe {
+                let mut v = x;
+                assert_eq!($load(&v, 5), x, "{}({})", stringify!($load), x);
+                $store(&mut v, y, 5);
+                assert_eq!(v, y, "{}({})", stringify!($store), y);
+                assert_eq!($exchange(&mut v, x, 5), y, "{}({}, {})", stringify!($exchange), y, x);
+                assert_eq!(v, x);
+
+                let mut expected = x;
+                assert!($compare_exchange(&mut v, &mut expected, y, 5, 5));
+                assert_eq!((v, expected), (y, x));
+                let mut expected = !y;
+                assert!(!$compare_exchange(&mut v, &mut expected, x, 5, 5));
+                assert_eq!((v, expected), (y, y));
+
+                let ops: [(unsafe extern "C" fn(*mut $ty, $ty, _) -> $ty, $ty, &str); 6] = [
+                    ($add, x.wrapping_add(y), stringify!($add)),
+                    ($sub, x.wrapping_sub(y), stringify!($sub)),
+                    ($and, x & y, stringify!($and)),
+                    ($or, x | y, stringify!($or)),
+                    ($xor, x ^ y, stringify!($xor)),
+                    ($nand, !(x & y), stringify!($nand)),
+                ];
+                for &(op, res, name) in ops.iter() {
+                    let mut v = x;
+                    assert_eq!(op(&mut v, y, 5), x, "{}({}, {})", name, x, y);
+                    assert_eq!(v, res, "{}({}, {})", name, x, y);
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn atomic_sized() {
+    sized!(
+        u8, __atomic_load_1, __atomic_store_1, __atomic_exchange_1, __atomic_compare_exchange_1,
+        __atomic_fetch_add_1, __atomic_fetch_sub_1, __atomic_fetch_and_1, __atomic_fetch_or_1,
+        __atomic_fetch_xor_1, __atomic_fetch_nand_1;
+        u16, __atomic_load_2, __atomic_store_2, __atomic_exchange_2, __atomic_compare_exchange_2,
+        __atomic_fetch_add_2, __atomic_fetch_sub_2, __atomic_fetch_and_2, __atomic_fetch_or_2,
+        __atomic_fetch_xor_2, __atomic_fetch_nand_2;
+        u32, __atomic_load_4, __atomic_store_4, __atomic_exchange_4, __atomic_compare_exchange_4,
+        __atomic_fetch_add_4, __atomic_fetch_sub_4, __atomic_fetch_and_4, __atomic_fetch_or_4,
+        __atomic_fetch_xor_4, __atomic_fetch_nand_4;
+        u64, __atomic_load_8, __atomic_store_8, __atomic_exchange_8, __atomic_compare_exchange_8,
+        __atomic_fetch_add_8, __atomic_fetch_sub_8, __atomic_fetch_and_8, __atomic_fetch_or_8,
+        __atomic_fetch_xor_8, __atomic_fetch_nand_8;
+        u128, __atomic_load_16, __atomic_store_16, __atomic_exchange_16,
+        __atomic_compare_exchange_16, __atomic_fetch_add_16, __atomic_fetch_sub_16,
+        __atomic_fetch_and_16, __atomic_fetch_or_16, __atomic_fetch_xor_16,
+        __atomic_fetch_nand_16;
+    );
+}
+
+#[test]
+fn atomic_generic() {
+    unsafe {
+        // An odd size, which only the generic functions handle
+        let mut v = [1u8, 2, 3];
+        let mut out = [0u8; 3];
+        __atomic_load(3, v.as_ptr(), out.as_mut_ptr(), 5);
+        assert_eq!(out, [1, 2, 3]);
+        __atomic_store(3, v.as_mut_ptr(), [4, 5, 6].as_ptr(), 5);
+        assert_eq!(v, [4, 5, 6]);
+        __atomic_exchange(3, v.as_mut_ptr(), [7, 8, 9].as_ptr(), out.as_mut_ptr(), 5);
+        assert_eq!((v, out), ([7, 8, 9], [4, 5, 6]));
+
+        let mut expected = [7u8, 8, 9];
+        assert!(__atomic_compare_exchange(
+            3,
+            v.as_mut_ptr(),
+            expected.as_mut_ptr(),
+            [1, 2, 3].as_ptr(),
+            5,
+            5
+        ));
+        assert_eq!((v, expected), ([1, 2, 3], [7, 8, 9]));
+        assert!(!__atomic_compare_exchange(
+            3,
+            v.as_mut_ptr(),
+            expected.as_mut_ptr(),
+            [4, 5, 6].as_ptr(),
+            5,
+            5
+        ));
+        assert_eq!((v, expected), ([1, 2, 3], [1, 2, 3]));
+    }
+}
+
+/// An integer shared between threads, which is only accessed through the atomic functions
+struct Shared<T>(*mut T);
+
+unsafe impl<T> Send for Shared<T> {}
+unsafe impl<T> Sync for Shared<T> {}
+
+#[test]
+fn atomic_contention() {
+    const THREADS: usize = 4;
+    const ITERATIONS: u128 = 100_000;
+
+    let shared = Arc::new(Shared(Box::into_raw(Box::new(0u128))));
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let shared = shared.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || unsafe {
+                barrier.wait();
+                for i in 0..ITERATIONS {
+                    // Alternates between the carry-heavy addition and a compare-exchange loop
+                    if i % 2 == 0 {
+                        __atomic_fetch_add_16(shared.0, 1 << 64 | 1, 5);
+                    } else {
+                        let mut old = __atomic_load_16(shared.0, 5);
+                        while !__atomic_compare_exchange_16(
+                            shared.0,
+                            &mut old,
+                            old.wrapping_add(1 << 64 | 1),
+                            5,
+                            5,
+                        ) {}
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let total = THREADS as u128 * ITERATIONS;
+    unsafe {
+        assert_eq!(__atomic_load_16(shared.0, 5), total << 64 | total);
+        drop(Box::from_raw(shared.0));
+    }
+}
+
+#[test]
+fn atomic_generic_lock_free() {
+    const THREADS: usize = 4;
+    const ITERATIONS: u64 = 100_000;
+
+    // The generic functions have to use the same instructions as the sized ones on a lock-free
+    // object, or the updates of one would be lost to the other.
+    let shared = Arc::new(Shared(Box::into_raw(Box::new(0u64))));
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let shared = shared.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || unsafe {
+                barrier.wait();
+                for i in 0..ITERATIONS {
+                    if i % 2 == 0 {
+                        __atomic_fetch_add_8(shared.0, 1, 5);
+                    } else {
+                        let mut old = 0u64;
+                        __atomic_load(8, shared.0 as *const u8, &mut old as *mut u64 as *mut u8, 5);
+                        while !__atomic_compare_exchange(
+                            8,
+                            shared.0 as *mut u8,
+                            &mut old as *mut u64 as *mut u8,
+                            &(old + 1) as *const u64 as *const u8,
+                            5,
+                            5,
+                        ) {}
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    unsafe {
+        assert_eq!(__atomic_load_8(shared.0, 5), THREADS as u64 * ITERATIONS);
+        drop(Box::from_raw(shared.0));
+    }
+}
+
+#[cfg(feature = "critical-section")]
+mod critical_section {
+    use std::hint::spin_loop;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use compiler_builtins::atomic::*;
+
+    static LOCKED: AtomicBool = AtomicBool::new(false);
+    static ENTERED: AtomicUsize = AtomicUsize::new(0);
+
+    #[no_mangle]
+    extern "C" fn __rust_critical_section_acquire() -> usize {
+        while LOCKED.swap(true, Ordering::Acquire) {
+            spin_loop();
+        }
+        ENTERED.fetch_add(1, Ordering::Relaxed);
+        0
+    }
+
+    #[no_mangle]
+    extern "C" fn __rust_critical_section_release(_state: usize) {
+        LOCKED.store(false, Ordering::Release);
+    }
+
+    #[test]
+    fn atomic_critical_section() {
+        let entered = ENTERED.load(Ordering::Relaxed);
+        let mut v = 1u128;
+        unsafe {
+            assert_eq!(__atomic_exchange_16(&mut v, 2, 5), 1);
+        }
+        assert_eq!(v, 2);
+        assert!(ENTERED.load(Ordering::Relaxed) > entered);
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(unused_macros)]
+#![cfg_attr(target_arch = "arm", feature(asm))]
 
 use testcrate::*;
 
@@ -76,7 +77,7 @@ fn float_comparisons_arm() {
         __aeabi_dcmpeq, __aeabi_dcmpge, __aeabi_dcmpgt, __aeabi_dcmple, __aeabi_dcmplt,
         __aeabi_fcmpeq, __aeabi_fcmpge, __aeabi_fcmpgt, __aeabi_fcmple, __aeabi_fcmplt, __eqdf2vfp,
         __eqsf2vfp, __gedf2vfp, __gesf2vfp, __gtdf2vfp, __gtsf2vfp, __ledf2vfp, __lesf2vfp,
-        __ltdf2vfp, __ltsf2vfp, __nedf2vfp, __nesf2vfp,
+        __ltdf2vfp, __ltsf2vfp, __nedf2vfp, __nesf2vfp, __unorddf2vfp, __unordsf2vfp,
     };
 
     fuzz_float_2(N, |x: f32, y: f32| {
@@ -92,6 +93,7 @@ fn float_comparisons_arm() {
             0, x >= y, __gesf2vfp;
             0, x > y, __gtsf2vfp;
             1, x != y, __nesf2vfp;
+            1, false, __unordsf2vfp;
         );
     });
     fuzz_float_2(N, |x: f64, y: f64| {
@@ -107,6 +109,164 @@ fn float_comparisons_arm() {
             0, x >= y, __gedf2vfp;
             0, x > y, __gtdf2vfp;
             1, x != y, __nedf2vfp;
+            1, false, __unorddf2vfp;
         );
     });
 }
+
+/// Calls a flag-returning comparison with `args` in r0-r3, checks that it preserved them and
+/// returns the Z and C flags
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+fn call_flags(f: unsafe extern "C" fn(), args: [u32; 4]) -> (bool, bool) {
+    let mut regs = args;
+    let apsr: u32;
+    unsafe {
+        asm!(
+            "blx {f}",
+            "mrs {apsr}, apsr",
+            f = in(reg) f,
+            apsr = lateout(reg) apsr,
+            inout("r0") regs[0],
+            inout("r1") regs[1],
+            inout("r2") regs[2],
+            inout("r3") regs[3],
+            out("r12") _,
+            out("lr") _,
+        );
+    }
+    assert_eq!(regs, args);
+    (apsr & (1 << 30) != 0, apsr & (1 << 29) != 0)
+}
+
+/// The Z and C flags of `__aeabi_cfcmple(x, y)` and friends
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+fn expected_flags<F: PartialOrd>(x: F, y: F) -> (bool, bool) {
+    if x < y {
+        (false, false)
+    } else if x == y {
+        (true, true)
+    } else {
+        (false, true)
+    }
+}
+
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+fn f64_args(x: f64, y: f64) -> [u32; 4] {
+    let (x, y) = (x.to_bits(), y.to_bits());
+    if cfg!(target_endian = "big") {
+        [(x >> 32) as u32, x as u32, (y >> 32) as u32, y as u32]
+    } else {
+        [x as u32, (x >> 32) as u32, y as u32, (y >> 32) as u32]
+    }
+}
+
+#[cfg(all(target_arch = "arm", not(feature = "no-asm")))]
+#[test]
+fn float_flag_comparisons_arm() {
+    use compiler_builtins::arm::{
+        __aeabi_cdcmpeq, __aeabi_cdcmple, __aeabi_cdrcmple, __aeabi_cfcmpeq, __aeabi_cfcmple,
+        __aeabi_cfrcmple,
+    };
+    use compiler_builtins::float::cmp::{__aeabi_cdcmpeq_check_nan, __aeabi_cfcmpeq_check_nan};
+
+    fuzz_float_2(N, |x: f32, y: f32| {
+        // r2 and r3 are not arguments, but have to be preserved as well.
+        let args = [x.to_bits(), y.to_bits(), 0x0123_4567, 0x89ab_cdef];
+        let rargs = [y.to_bits(), x.to_bits(), 0x0123_4567, 0x89ab_cdef];
+        assert_eq!(call_flags(__aeabi_cfcmpeq, args), expected_flags(x, y));
+        assert_eq!(call_flags(__aeabi_cfcmple, args), expected_flags(x, y));
+        assert_eq!(call_flags(__aeabi_cfrcmple, rargs), expected_flags(x, y));
+        assert_eq!(
+            __aeabi_cfcmpeq_check_nan(x, y) != 0,
+            x.is_nan() || y.is_nan()
+        );
+    });
+    fuzz_float_2(N, |x: f64, y: f64| {
+        assert_eq!(
+            call_flags(__aeabi_cdcmpeq, f64_args(x, y)),
+            expected_flags(x, y)
+        );
+        assert_eq!(
+            call_flags(__aeabi_cdcmple, f64_args(x, y)),
+            expected_flags(x, y)
+        );
+        assert_eq!(
+            call_flags(__aeabi_cdrcmple, f64_args(y, x)),
+            expected_flags(x, y)
+        );
+        assert_eq!(
+            __aeabi_cdcmpeq_check_nan(x, y) != 0,
+            x.is_nan() || y.is_nan()
+        );
+    });
+}
+
+macro_rules! int_cmp {
+    ($($i:ty, $fn:ident);*;) => {
+        $(
+            let edges = [<$i>::MIN, <$i>::MIN + 1, 0, 1, <$i>::MAX - 1, <$i>::MAX, !0];
+            for &x in edges.iter() {
+                for &y in edges.iter() {
+                    assert_eq!($fn(x, y), x.cmp(&y) as i32 + 1, "{}({}, {})", stringify!($fn), x, y);
+                }
+            }
+            fuzz_2(N, |x: $i, y: $i| {
+                assert_eq!($fn(x, y), x.cmp(&y) as i32 + 1, "{}({}, {})", stringify!($fn), x, y);
+            });
+        )*
+    };
+}
+
+#[test]
+fn int_comparisons() {
+    use compiler_builtins::int::cmp::{__cmpdi2, __cmpti2, __ucmpdi2, __ucmpti2};
+
+    int_cmp!(
+        i64, __cmpdi2;
+        u64, __ucmpdi2;
+        i128, __cmpti2;
+        u128, __ucmpti2;
+    );
+}
+
+#[cfg(target_arch = "arm")]
+#[test]
+fn int_comparisons_arm() {
+    use compiler_builtins::int::cmp::{__aeabi_lcmp, __aeabi_ulcmp};
+
+    fuzz_2(N, |x: i64, y: i64| {
+        assert_eq!(
+            __aeabi_lcmp(x, y),
+            x.cmp(&y) as i32,
+            "__aeabi_lcmp({}, {})",
+            x,
+            y
+        );
+        let (x, y) = (x as u64, y as u64);
+        assert_eq!(
+            __aeabi_ulcmp(x, y),
+            x.cmp(&y) as i32,
+            "__aeabi_ulcmp({}, {})",
+            x,
+            y
+        );
+    });
+}
+
+#[test]
+fn int_negation() {
+    use compiler_builtins::int::cmp::{__negdi2, __negti2};
+
+    for &x in [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX].iter() {
+        assert_eq!(__negdi2(x), x.wrapping_neg(), "__negdi2({})", x);
+    }
+    for &x in [i128::MIN, i128::MIN + 1, -1, 0, 1, i128::MAX].iter() {
+        assert_eq!(__negti2(x), x.wrapping_neg(), "__negti2({})", x);
+    }
+    fuzz(N, |x: i64| {
+        assert_eq!(__negdi2(x), x.wrapping_neg(), "__negdi2({})", x)
+    });
+    fuzz(N, |x: i128| {
+        assert_eq!(__negti2(x), x.wrapping_neg(), "__negti2({})", x)
+    });
+}
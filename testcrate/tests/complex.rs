@@ -0,0 +1,114 @@
+#![allow(unused_macros)]
+// The system libgcc versions are used as the reference, `_Complex` has no Rust equivalent.
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+use compiler_builtins::float::complex::{__divdc3, __divsc3, __muldc3, __mulsc3, Complex};
+use compiler_builtins::float::Float;
+use testcrate::*;
+
+extern "C" {
+    #[link_name = "__mulsc3"]
+    fn gcc_mulsc3(a: f32, b: f32, c: f32, d: f32) -> Complex<f32>;
+    #[link_name = "__muldc3"]
+    fn gcc_muldc3(a: f64, b: f64, c: f64, d: f64) -> Complex<f64>;
+    #[link_name = "__divsc3"]
+    fn gcc_divsc3(a: f32, b: f32, c: f32, d: f32) -> Complex<f32>;
+    #[link_name = "__divdc3"]
+    fn gcc_divdc3(a: f64, b: f64, c: f64, d: f64) -> Complex<f64>;
+}
+
+/// The divisors that every fuzzed dividend (and multiplier) is combined with, chosen to hit the
+/// special cases of Annex G together with the edge cases of the fuzzer.
+macro_rules! operands {
+    ($fX:ident, $x:ident, $y:ident) => {
+        [
+            ($y, $x),
+            ($x, -$y),
+            (0.0, 0.0),
+            (-0.0, 0.0),
+            (1.0, 0.0),
+            (0.0, -1.0),
+            (3.0, 4.0),
+            ($fX::INFINITY, 0.0),
+            (2.0, -$fX::INFINITY),
+            ($fX::INFINITY, $fX::NAN),
+            ($fX::NAN, 1.0),
+            ($fX::MAX, $fX::MAX),
+            ($fX::MIN_POSITIVE, -$fX::MIN_POSITIVE),
+            (<$fX>::from_bits(1), 1.0),
+        ]
+    };
+}
+
+macro_rules! mul {
+    ($fX:ident, $fn:ident, $gcc:ident) => {
+        fuzz_float_2(N, |x: $fX, y: $fX| {
+            for &(c, d) in operands!($fX, x, y).iter() {
+                let res0 = unsafe { $gcc(x, y, c, d) };
+                let res1 = $fn(x, y, c, d);
+                if !Float::eq_repr(res0.re, res1.re) || !Float::eq_repr(res0.im, res1.im) {
+                    panic!(
+                        "{}({:?}, {:?}, {:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                        stringify!($fn),
+                        x,
+                        y,
+                        c,
+                        d,
+                        res0,
+                        res1
+                    );
+                }
+            }
+        });
+    };
+}
+
+#[test]
+fn float_mul_complex() {
+    mul!(f32, __mulsc3, gcc_mulsc3);
+    mul!(f64, __muldc3, gcc_muldc3);
+}
+
+macro_rules! div {
+    ($fX:ident, $fn:ident, $gcc:ident) => {
+        fuzz_float_2(N, |x: $fX, y: $fX| {
+            for &(c, d) in operands!($fX, x, y).iter() {
+                let res0: Complex<$fX> = unsafe { $gcc(x, y, c, d) };
+                let res1: Complex<$fX> = $fn(x, y, c, d);
+                // libgcc uses a different algorithm, so finite results are only required to be
+                // within a few ulps of the larger part. Non-finite results have to match.
+                let scale = res0.re.abs().max(res0.im.abs());
+                let tolerance = if scale.is_finite() {
+                    (scale * 8.0 * $fX::EPSILON).max(8.0 * <$fX>::from_bits(1))
+                } else {
+                    0.0
+                };
+                let close = |x: $fX, y: $fX| {
+                    if x.is_finite() && y.is_finite() {
+                        (x - y).abs() <= tolerance
+                    } else {
+                        Float::eq_repr(x, y)
+                    }
+                };
+                if !close(res0.re, res1.re) || !close(res0.im, res1.im) {
+                    panic!(
+                        "{}({:?}, {:?}, {:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                        stringify!($fn),
+                        x,
+                        y,
+                        c,
+                        d,
+                        res0,
+                        res1
+                    );
+                }
+            }
+        });
+    };
+}
+
+#[test]
+fn float_div_complex() {
+    div!(f32, __divsc3, gcc_divsc3);
+    div!(f64, __divdc3, gcc_divdc3);
+}
@@ -81,15 +81,21 @@ fn int_to_float() {
 }
 
 macro_rules! f_to_i {
-    ($x:ident, $($f:ty, $fn:ident);*;) => {
+    ($x:ident, $($f:ty, $fn:ident, $sat:ident);*;) => {
         $(
-            // it is undefined behavior in the first place to do conversions with NaNs
-            if !$x.is_nan() {
-                let conv0 = $x as $f;
-                let conv1: $f = $fn($x);
-                if conv0 != conv1 {
-                    panic!("{}({}): std: {}, builtins: {}", stringify!($fn), $x, conv0, conv1);
-                }
+            let conv0 = $x as $f;
+            let conv1: $f = $fn($x);
+            let conv2: $f = $sat($x);
+            if conv0 != conv1 || conv0 != conv2 {
+                panic!(
+                    "{}({}): std: {}, builtins: {}, {}: {}",
+                    stringify!($fn),
+                    $x,
+                    conv0,
+                    conv1,
+                    stringify!($sat),
+                    conv2
+                );
             }
         )*
     };
@@ -100,26 +106,56 @@ fn float_to_int() {
     use compiler_builtins::float::conv::{
         __fixdfdi, __fixdfsi, __fixdfti, __fixsfdi, __fixsfsi, __fixsfti, __fixunsdfdi,
         __fixunsdfsi, __fixunsdfti, __fixunssfdi, __fixunssfsi, __fixunssfti,
+        __rust_f32_to_i128_sat, __rust_f32_to_i32_sat, __rust_f32_to_i64_sat,
+        __rust_f32_to_u128_sat, __rust_f32_to_u32_sat, __rust_f32_to_u64_sat,
+        __rust_f64_to_i128_sat, __rust_f64_to_i32_sat, __rust_f64_to_i64_sat,
+        __rust_f64_to_u128_sat, __rust_f64_to_u32_sat, __rust_f64_to_u64_sat,
     };
 
     fuzz_float(N, |x: f32| {
         f_to_i!(x,
-            u32, __fixunssfsi;
-            u64, __fixunssfdi;
-            u128, __fixunssfti;
-            i32, __fixsfsi;
-            i64, __fixsfdi;
-            i128, __fixsfti;
+            u32, __fixunssfsi, __rust_f32_to_u32_sat;
+            u64, __fixunssfdi, __rust_f32_to_u64_sat;
+            u128, __fixunssfti, __rust_f32_to_u128_sat;
+            i32, __fixsfsi, __rust_f32_to_i32_sat;
+            i64, __fixsfdi, __rust_f32_to_i64_sat;
+            i128, __fixsfti, __rust_f32_to_i128_sat;
         );
     });
     fuzz_float(N, |x: f64| {
         f_to_i!(x,
-            u32, __fixunsdfsi;
-            u64, __fixunsdfdi;
-            u128, __fixunsdfti;
-            i32, __fixdfsi;
-            i64, __fixdfdi;
-            i128, __fixdfti;
+            u32, __fixunsdfsi, __rust_f64_to_u32_sat;
+            u64, __fixunsdfdi, __rust_f64_to_u64_sat;
+            u128, __fixunsdfti, __rust_f64_to_u128_sat;
+            i32, __fixdfsi, __rust_f64_to_i32_sat;
+            i64, __fixdfdi, __rust_f64_to_i64_sat;
+            i128, __fixdfti, __rust_f64_to_i128_sat;
         );
     });
 }
+
+#[cfg(target_arch = "arm")]
+#[test]
+fn conv_arm() {
+    use compiler_builtins::float::conv::{
+        __fixdfsivfp, __fixsfsivfp, __fixunsdfsivfp, __fixunssfsivfp, __floatsidfvfp,
+        __floatsisfvfp, __floatunssidfvfp, __floatunssisfvfp,
+    };
+
+    fuzz(N, |x: i32| {
+        assert_eq!(__floatsisfvfp(x).to_bits(), (x as f32).to_bits());
+        assert_eq!(__floatsidfvfp(x).to_bits(), (x as f64).to_bits());
+    });
+    fuzz(N, |x: u32| {
+        assert_eq!(__floatunssisfvfp(x).to_bits(), (x as f32).to_bits());
+        assert_eq!(__floatunssidfvfp(x).to_bits(), (x as f64).to_bits());
+    });
+    fuzz_float(N, |x: f32| {
+        assert_eq!(__fixsfsivfp(x), x as i32);
+        assert_eq!(__fixunssfsivfp(x), x as u32);
+    });
+    fuzz_float(N, |x: f64| {
+        assert_eq!(__fixdfsivfp(x), x as i32);
+        assert_eq!(__fixunsdfsivfp(x), x as u32);
+    });
+}
@@ -1,7 +1,13 @@
 #![allow(unused_macros)]
+#![feature(simd_ffi)]
 
-use compiler_builtins::int::sdiv::{__divmoddi4, __divmodsi4, __divmodti4};
-use compiler_builtins::int::udiv::{__udivmoddi4, __udivmodsi4, __udivmodti4, u128_divide_sparc};
+use compiler_builtins::int::sdiv::{
+    __divhi3, __divmoddi4, __divmodhi4, __divmodsi4, __divmodti4, __modhi3,
+};
+use compiler_builtins::int::u128_div_rem;
+use compiler_builtins::int::udiv::{
+    __udivhi3, __udivmoddi4, __udivmodhi4, __udivmodsi4, __udivmodti4, __umodhi3, u128_divide_sparc,
+};
 use testcrate::*;
 
 // Division algorithms have by far the nastiest and largest number of edge cases, and experience shows
@@ -75,10 +81,40 @@ macro_rules! test {
     };
 }
 
+test!(16, u16, i16, div_rem_hi4, __udivmodhi4, __divmodhi4);
 test!(32, u32, i32, div_rem_si4, __udivmodsi4, __divmodsi4);
 test!(64, u64, i64, div_rem_di4, __udivmoddi4, __divmoddi4);
 test!(128, u128, i128, div_rem_ti4, __udivmodti4, __divmodti4);
 
+#[test]
+fn div_rem_hi3() {
+    fuzz_2(N, |lhs: u16, rhs: u16| {
+        if rhs == 0 {
+            return;
+        }
+        assert_eq!(
+            __udivhi3(lhs, rhs),
+            lhs / rhs,
+            "__udivhi3({}, {})",
+            lhs,
+            rhs
+        );
+        assert_eq!(
+            __umodhi3(lhs, rhs),
+            lhs % rhs,
+            "__umodhi3({}, {})",
+            lhs,
+            rhs
+        );
+        let lhs = lhs as i16;
+        let rhs = rhs as i16;
+        let quo = lhs.wrapping_div(rhs);
+        let rem = lhs.wrapping_rem(rhs);
+        assert_eq!(__divhi3(lhs, rhs), quo, "__divhi3({}, {})", lhs, rhs);
+        assert_eq!(__modhi3(lhs, rhs), rem, "__modhi3({}, {})", lhs, rhs);
+    });
+}
+
 #[test]
 fn divide_sparc() {
     fuzz_2(N, |lhs, rhs| {
@@ -103,20 +139,37 @@ fn divide_sparc() {
     });
 }
 
+#[test]
+fn div_rem_const() {
+    // These are evaluated at compile time, where the `div` instruction used on x86_64 is not
+    // available.
+    const SMALL: (u128, u128) = u128_div_rem(1 << 70, 3);
+    const LARGE: (u128, u128) = u128_div_rem(u128::MAX, 0xffff_ffff_ffff_ffff_ffff);
+    const EQUAL: (u128, u128) = u128_div_rem(1 << 100, 1 << 100);
+    const ZERO: (u128, u128) = u128_div_rem(0, 7);
+    let cases = [
+        (1 << 70, 3, SMALL),
+        (u128::MAX, 0xffff_ffff_ffff_ffff_ffff, LARGE),
+        (1 << 100, 1 << 100, EQUAL),
+        (0, 7, ZERO),
+    ];
+    for &(lhs, rhs, res) in cases.iter() {
+        assert_eq!(res, (lhs / rhs, lhs % rhs), "{} / {}", lhs, rhs);
+        assert_eq!(res, u128_div_rem(lhs, rhs), "{} / {}", lhs, rhs);
+    }
+}
+
 macro_rules! float {
     ($($i:ty, $fn:ident);*;) => {
         $(
             fuzz_float_2(N, |x: $i, y: $i| {
                 let quo0 = x / y;
                 let quo1: $i = $fn(x, y);
-                // division of subnormals is not currently handled
-                if !(Float::is_subnormal(quo0) || Float::is_subnormal(quo1)) {
-                    if !Float::eq_repr(quo0, quo1) {
-                        panic!(
-                            "{}({}, {}): std: {}, builtins: {}",
-                            stringify!($fn), x, y, quo0, quo1
-                        );
-                    }
+                if !Float::eq_repr(quo0, quo1) {
+                    panic!(
+                        "{}({}, {}): std: {}, builtins: {}",
+                        stringify!($fn), x, y, quo0, quo1
+                    );
                 }
             });
         )*
@@ -150,3 +203,36 @@ fn float_div_arm() {
         f64, __divdf3vfp;
     );
 }
+
+// There is no native `f128` to compare against, so the libgcc implementation is used as the
+// reference.
+#[cfg(all(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "mips64"
+    ),
+    target_os = "linux"
+))]
+#[test]
+fn float_div_f128() {
+    use compiler_builtins::float::{div::__divtf3, f128, Float};
+
+    // `f128` is passed like `__float128`, even though the lint cannot tell.
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "__divtf3"]
+        fn gcc_divtf3(a: f128, b: f128) -> f128;
+    }
+
+    fuzz_float_2(N, |x: f128, y: f128| {
+        let quo0 = unsafe { gcc_divtf3(x, y) };
+        let quo1 = __divtf3(x, y);
+        if !Float::eq_repr(quo0, quo1) {
+            panic!(
+                "__divtf3({:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                x, y, quo0, quo1
+            );
+        }
+    });
+}
@@ -0,0 +1,193 @@
+use compiler_builtins::float::double_double::*;
+use compiler_builtins::float::f128;
+use rand_xoshiro::rand_core::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro128StarStar;
+use testcrate::*;
+
+/// Returns a random canonical double-double with a head in `[2^min_exp, 2^(max_exp + 1))` in
+/// magnitude. The tail is zero or has its 53 bits right below the bits of the head, so that the
+/// exact value fits in an `f128`.
+fn random(rng: &mut Xoshiro128StarStar, min_exp: i32, max_exp: i32) -> DoubleDouble {
+    let exp = min_exp + (rng.next_u32() % (max_exp - min_exp + 1) as u32) as i32;
+    let significand = (rng.next_u64() >> 11) | (1 << 52);
+    let mut hi = significand as f64 * 2.0f64.powi(exp - 52);
+    if rng.next_u32() & 1 != 0 {
+        hi = -hi;
+    }
+    let lo = if rng.next_u32() & 3 == 0 {
+        0.0
+    } else {
+        (rng.next_u64() as i64 >> 11) as f64 * 2.0f64.powi(exp - 52 - 54)
+    };
+    DoubleDouble { hi, lo }
+}
+
+/// Converts a zero or normal `f64` to an `f128` exactly
+fn tf(x: f64) -> f128 {
+    let bits = x.to_bits() as u128;
+    let sign = bits >> 63 << 127;
+    if x == 0.0 {
+        return f128::from_bits(sign);
+    }
+    let exponent = ((bits >> 52) & 0x7ff) + 16383 - 1023;
+    f128::from_bits(sign | exponent << 112 | (bits & ((1 << 52) - 1)) << 60)
+}
+
+fn value(x: DoubleDouble) -> f128 {
+    tf(x.hi) + tf(x.lo)
+}
+
+fn exponent(x: f128) -> i32 {
+    (x.to_bits() >> 112) as i32 & 0x7fff
+}
+
+fn same(x: DoubleDouble, y: DoubleDouble) -> bool {
+    (x.hi.to_bits() == y.hi.to_bits() || (x.hi.is_nan() && y.hi.is_nan()))
+        && x.lo.to_bits() == y.lo.to_bits()
+}
+
+macro_rules! arith {
+    ($($op:ident, $sym:tt;)*) => {
+        $(
+            let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+            for _ in 0..N {
+                let x = random(&mut rng, -40, 40);
+                let y = random(&mut rng, -40, 40);
+                let res = $op(x, y);
+                let expected = value(x) $sym value(y);
+                let err = value(res) - expected;
+                // The double-double operations are not correctly rounded, but within a few ulps of
+                // the 106 bit significand.
+                if res.hi + res.lo != res.hi
+                    || (err.to_bits() << 1 != 0 && exponent(err) > exponent(expected) - 100)
+                {
+                    panic!(
+                        "{}({:?}, {:?}): expected {:?}, builtins: {:?}",
+                        stringify!($op),
+                        x,
+                        y,
+                        expected,
+                        res
+                    );
+                }
+            }
+        )*
+    };
+}
+
+#[test]
+fn double_double_arith() {
+    arith!(
+        add, +;
+        mul, *;
+        div, /;
+    );
+}
+
+#[test]
+fn double_double_special() {
+    let dd = |hi: f64, lo: f64| DoubleDouble { hi, lo };
+    let inf = f64::INFINITY;
+    let cases = [
+        (add(dd(0.0, 0.0), dd(-0.0, 0.0)), dd(0.0, 0.0)),
+        (add(dd(-0.0, 0.0), dd(-0.0, 0.0)), dd(-0.0, 0.0)),
+        (add(dd(1.0, 1e-20), dd(-1.0, -1e-20)), dd(0.0, 0.0)),
+        (add(dd(inf, 0.0), dd(1.0, 1e-20)), dd(inf, 0.0)),
+        (add(dd(inf, 0.0), dd(-inf, 0.0)), dd(f64::NAN, 0.0)),
+        (add(dd(f64::MAX, 0.0), dd(f64::MAX, 0.0)), dd(inf, 0.0)),
+        (add(dd(f64::NAN, 0.0), dd(1.0, 0.0)), dd(f64::NAN, 0.0)),
+        (mul(dd(0.0, 0.0), dd(-3.0, 1e-16)), dd(-0.0, 0.0)),
+        (mul(dd(inf, 0.0), dd(-3.0, 1e-16)), dd(-inf, 0.0)),
+        (mul(dd(inf, 0.0), dd(0.0, 0.0)), dd(f64::NAN, 0.0)),
+        (mul(dd(f64::MAX, 0.0), dd(2.0, 0.0)), dd(inf, 0.0)),
+        (div(dd(1.0, 1e-20), dd(0.0, 0.0)), dd(inf, 0.0)),
+        (div(dd(-0.0, 0.0), dd(3.0, 0.0)), dd(-0.0, 0.0)),
+        (div(dd(1.0, 0.0), dd(inf, 0.0)), dd(0.0, 0.0)),
+        (div(dd(0.0, 0.0), dd(0.0, 0.0)), dd(f64::NAN, 0.0)),
+        (div(dd(6.0, 0.0), dd(3.0, 0.0)), dd(2.0, 0.0)),
+    ];
+    for (i, &(res, expected)) in cases.iter().enumerate() {
+        assert!(
+            same(res, expected),
+            "case {}: {:?} != {:?}",
+            i,
+            res,
+            expected
+        );
+    }
+}
+
+/// Returns the integer part of the magnitude of `x`, which must be smaller than `2^64`
+fn int_part(x: f128) -> u64 {
+    let exponent = exponent(x) - 16383;
+    let significand = (x.to_bits() & ((1 << 112) - 1)) | (1 << 112);
+    if exponent < 0 {
+        0
+    } else {
+        (significand >> (112 - exponent)) as u64
+    }
+}
+
+#[test]
+fn double_double_to_int() {
+    let mut rng = Xoshiro128StarStar::seed_from_u64(0);
+    for _ in 0..N {
+        let x = random(&mut rng, -2, 62);
+        let i = int_part(value(x)) as i64;
+        let expected = if x.hi < 0.0 { -i } else { i };
+        assert_eq!(to_i64(x), expected, "to_i64({:?})", x);
+
+        let x = random(&mut rng, -2, 63);
+        let expected = if x.hi < 0.0 { 0 } else { int_part(value(x)) };
+        assert_eq!(to_u64(x), expected, "to_u64({:?})", x);
+    }
+
+    let dd = |hi: f64, lo: f64| DoubleDouble { hi, lo };
+    let two_p63 = 9223372036854775808.0;
+    let signed = [
+        (dd(5.0, -1e-30), 4),
+        (dd(-5.0, 1e-30), -4),
+        (dd(1.0, -1e-30), 0),
+        (dd(-1.0, 1e-30), 0),
+        (dd(0.5, 0.0), 0),
+        (dd(two_p63, -1.0), i64::MAX),
+        (dd(two_p63, 0.0), i64::MAX),
+        (dd(-two_p63, 0.5), i64::MIN + 1),
+        (dd(-two_p63, 1024.0), i64::MIN + 1024),
+        (dd(-two_p63, 1e-30), i64::MIN + 1),
+        (dd(-two_p63, -1.0), i64::MIN),
+        (dd(f64::INFINITY, 0.0), i64::MAX),
+        (dd(-f64::INFINITY, 0.0), i64::MIN),
+    ];
+    for &(x, expected) in signed.iter() {
+        assert_eq!(to_i64(x), expected, "to_i64({:?})", x);
+    }
+    let unsigned = [
+        (dd(5.0, -1e-30), 4),
+        (dd(1.0, -1e-30), 0),
+        (dd(-5.0, 0.0), 0),
+        (dd(two_p63 * 2.0, -1.0), u64::MAX),
+        (dd(two_p63, -0.5), (1 << 63) - 1),
+        (dd(f64::INFINITY, 0.0), u64::MAX),
+        (dd(-f64::INFINITY, 0.0), 0),
+    ];
+    for &(x, expected) in unsigned.iter() {
+        assert_eq!(to_u64(x), expected, "to_u64({:?})", x);
+    }
+}
+
+#[test]
+fn int_to_double_double() {
+    fuzz(N, |i: i64| {
+        let res = from_i64(i);
+        if res.hi + res.lo != res.hi || res.hi as i128 + res.lo as i128 != i as i128 {
+            panic!("from_i64({}): builtins: {:?}", i, res);
+        }
+    });
+    fuzz(N, |i: u64| {
+        let res = from_u64(i);
+        if res.hi + res.lo != res.hi || res.hi as i128 + res.lo as i128 != i as i128 {
+            panic!("from_u64({}): builtins: {:?}", i, res);
+        }
+    });
+}
@@ -0,0 +1,571 @@
+#![allow(unused_macros)]
+// The intrinsics have the C ABI of `long double`, which Rust cannot express, so they are called
+// through inline assembly. The x87 and the system libgcc are used as the reference. Without them,
+// the arithmetic of `f80` that they wrap is still checked against the x87.
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+#![feature(asm)]
+
+#[cfg(not(feature = "no-asm"))]
+use compiler_builtins::float::complex::Complex;
+#[cfg(not(feature = "no-asm"))]
+use compiler_builtins::float::extended::*;
+#[cfg(not(feature = "no-asm"))]
+use compiler_builtins::float::f128;
+use compiler_builtins::float::{f80, Float};
+use testcrate::*;
+
+#[cfg(not(feature = "no-asm"))]
+extern "C" {
+    #[link_name = "__powixf2"]
+    fn gcc_powixf2();
+    #[link_name = "__mulxc3"]
+    fn gcc_mulxc3();
+    #[link_name = "__divxc3"]
+    fn gcc_divxc3();
+    #[link_name = "__extendxftf2"]
+    fn gcc_extendxftf2();
+    #[link_name = "__trunctfxf2"]
+    fn gcc_trunctfxf2();
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// The registers that hold the result of an intrinsic
+struct Ret {
+    st: [f80; 2],
+    rax: u64,
+    rdx: u64,
+    xmm0: u128,
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// Calls `f` with the `long double` arguments `args` on the stack and the other arguments in
+/// `rdi`, `rsi` and `xmm0`, popping `pop` results from the x87 stack
+unsafe fn call(
+    f: unsafe extern "C" fn(),
+    args: &[f80],
+    rdi: u64,
+    rsi: u64,
+    xmm0: u128,
+    pop: usize,
+) -> Ret {
+    let mut st = [f80::ZERO; 2];
+    let rax: u64;
+    let rdx: u64;
+    let xmm0_out: core::arch::x86_64::__m128i;
+    asm!(
+        // Copy the arguments to the bottom of an aligned stack.
+        "mov %rsp, %r12",
+        "sub %r9, %rsp",
+        "and $-16, %rsp",
+        "xor %r11d, %r11d",
+        "2:",
+        "cmp %r9, %r11",
+        "jae 3f",
+        "mov (%r8,%r11), %r10",
+        "mov %r10, (%rsp,%r11)",
+        "add $8, %r11",
+        "jmp 2b",
+        "3:",
+        "call *%r13",
+        "mov %r12, %rsp",
+        "cmp $1, %r15",
+        "jb 4f",
+        "fstpt (%r14)",
+        "je 4f",
+        "fstpt 16(%r14)",
+        "4:",
+        in("r8") args.as_ptr(),
+        in("r9") args.len() * 16,
+        in("r13") f,
+        in("r14") st.as_mut_ptr(),
+        in("r15") pop,
+        out("r12") _,
+        in("rdi") rdi,
+        in("rsi") rsi,
+        out("rax") rax,
+        out("rdx") rdx,
+        inout("xmm0") core::mem::transmute::<u128, core::arch::x86_64::__m128i>(xmm0) => xmm0_out,
+        clobber_abi("C"),
+        options(att_syntax)
+    );
+    Ret {
+        st,
+        rax,
+        rdx,
+        xmm0: core::mem::transmute::<core::arch::x86_64::__m128i, u128>(xmm0_out),
+    }
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// Calls the intrinsic `f`, which returns a `long double`
+fn call_xf(f: unsafe extern "C" fn(), args: &[f80], rdi: u64, rsi: u64, xmm0: u128) -> f80 {
+    unsafe { call(f, args, rdi, rsi, xmm0, 1).st[0] }
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// Calls the intrinsic `f`, which returns an integer
+fn call_int(f: unsafe extern "C" fn(), args: &[f80]) -> u128 {
+    let ret = unsafe { call(f, args, 0, 0, 0, 0) };
+    (ret.rdx as u128) << 64 | ret.rax as u128
+}
+
+/// Returns `a $insn b` computed by the x87
+macro_rules! x87 {
+    ($insn:literal, $a:expr, $b:expr) => {{
+        let (a, b): (f80, f80) = ($a, $b);
+        let mut res = f80::ZERO;
+        unsafe {
+            asm!(
+                "fldt ({b})",
+                "fldt ({a})",
+                concat!($insn, " %st(1), %st"),
+                "fstpt ({res})",
+                "fstp %st(0)",
+                a = in(reg) &a,
+                b = in(reg) &b,
+                res = in(reg) &mut res,
+                out("st(0)") _,
+                out("st(1)") _,
+                options(att_syntax, nostack)
+            );
+        }
+        res
+    }};
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// Returns `i` converted by the x87, which is exact
+fn fild(i: i64) -> f80 {
+    let mut res = f80::ZERO;
+    unsafe {
+        asm!(
+            "fildq ({i})",
+            "fstpt ({res})",
+            i = in(reg) &i,
+            res = in(reg) &mut res,
+            out("st(0)") _,
+            options(att_syntax, nostack)
+        );
+    }
+    res
+}
+
+/// Returns the result of `fucomip` as `(less, equal, unordered)`
+fn fucomi(a: f80, b: f80) -> (bool, bool, bool) {
+    let (lt, eq, un): (u8, u8, u8);
+    unsafe {
+        asm!(
+            "fldt ({b})",
+            "fldt ({a})",
+            "fucomip %st(1), %st",
+            "fstp %st(0)",
+            "setb {lt}",
+            "sete {eq}",
+            "setp {un}",
+            a = in(reg) &a,
+            b = in(reg) &b,
+            lt = out(reg_byte) lt,
+            eq = out(reg_byte) eq,
+            un = out(reg_byte) un,
+            out("st(0)") _,
+            out("st(1)") _,
+            options(att_syntax, nostack)
+        );
+    }
+    (lt != 0, eq != 0, un != 0)
+}
+
+#[cfg(not(feature = "no-asm"))]
+const TWO64: f80 = f80::from_bits(0x403f << 64 | 1 << 63);
+
+#[cfg(not(feature = "no-asm"))]
+/// Returns `i` converted exactly
+fn from_u64(i: u64) -> f80 {
+    if i >> 63 == 0 {
+        fild(i as i64)
+    } else {
+        x87!("fadd", fild(i as i64), TWO64)
+    }
+}
+
+/// Returns whether `a` and `b` are the same float, or both NaN
+fn same(a: f80, b: f80) -> bool {
+    (a.is_nan() && b.is_nan()) || a.to_bits() == b.to_bits()
+}
+
+/// Floats that the x87 does not generate: an unnormal, a pseudo-denormal, a pseudo-infinity and
+/// a pseudo-NaN
+const NON_CANONICAL: [f80; 4] = [
+    f80::from_bits(0x3fff_7fff_ffff_ffff_ffff),
+    f80::from_bits(0x0000_8000_0000_0000_0001),
+    f80::from_bits(0x7fff_0000_0000_0000_0000),
+    f80::from_bits(0xffff_4000_0000_0000_0000),
+];
+
+/// Calls `f` with the fuzzed pairs of floats and the pairs with a non-canonical float
+fn fuzz_xf_2<E: Fn(f80, f80)>(f: E) {
+    fuzz_float_2(N, |x: f80, y: f80| f(x, y));
+    for &x in NON_CANONICAL.iter() {
+        for &y in [f80::ONE, f80::from_bits(1), x].iter() {
+            f(x, y);
+            f(y, x);
+        }
+    }
+}
+
+macro_rules! arith {
+    ($($fn:ident, $insn:literal;)*) => {
+        $(
+            fuzz_xf_2(|x: f80, y: f80| {
+                let res0 = x87!($insn, x, y);
+                let res1 = call_xf($fn::$fn, &[x, y], 0, 0, 0);
+                if !same(res0, res1) {
+                    panic!(
+                        "{}({:?}, {:?}): x87: {:?}, builtins: {:?}",
+                        stringify!($fn),
+                        x,
+                        y,
+                        res0,
+                        res1
+                    );
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn xf_ops() {
+    fuzz_xf_2(|x: f80, y: f80| {
+        let cases = [
+            ("add", x87!("fadd", x, y), x + y),
+            ("sub", x87!("fsub", x, y), x - y),
+            ("mul", x87!("fmul", x, y), x * y),
+            ("div", x87!("fdiv", x, y), x / y),
+        ];
+        for &(op, res0, res1) in cases.iter() {
+            if !same(res0, res1) {
+                panic!(
+                    "{}({:?}, {:?}): x87: {:?}, builtins: {:?}",
+                    op, x, y, res0, res1
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn xf_ord() {
+    fuzz_xf_2(|x: f80, y: f80| {
+        let (lt, eq, un) = fucomi(x, y);
+        let expected = if un {
+            None
+        } else if lt {
+            Some(core::cmp::Ordering::Less)
+        } else if eq {
+            Some(core::cmp::Ordering::Equal)
+        } else {
+            Some(core::cmp::Ordering::Greater)
+        };
+        assert_eq!(x.partial_cmp(&y), expected, "{:?}.partial_cmp({:?})", x, y);
+    });
+}
+
+#[test]
+fn xf_repr() {
+    // Every representation without the integer bit is the one of a canonical float.
+    fuzz(N, |r: u128| {
+        let r = r & ((1 << 79) - 1);
+        let x = f80::from_repr(r);
+        assert_eq!(x.repr(), r, "f80::from_repr({:#x}) = {:?}", r, x);
+    });
+    fuzz_float(N, |x: f80| {
+        let y = f80::from_repr(x.repr());
+        assert_eq!(y.to_bits(), x.to_bits(), "{:?} repacked as {:?}", x, y);
+    });
+    // The non-canonical floats are repacked as a float that the x87 treats the same.
+    for &x in NON_CANONICAL.iter() {
+        let y = f80::from_repr(x.repr());
+        let (_, eq, un) = fucomi(x, y);
+        assert!(
+            if un { y.is_nan() } else { eq },
+            "{:?} repacked as {:?}",
+            x,
+            y
+        );
+    }
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_arith() {
+    arith!(
+        __addxf3, "fadd";
+        __subxf3, "fsub";
+        __mulxf3, "fmul";
+        __divxf3, "fdiv";
+    );
+    fuzz_float(N, |x: f80| {
+        let res = call_xf(__negxf2::__negxf2, &[x], 0, 0, 0);
+        assert_eq!(res.to_bits(), x.to_bits() ^ 1 << 79, "__negxf2({:?})", x);
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_cmp() {
+    fuzz_xf_2(|x: f80, y: f80| {
+        let (lt, eq, un) = fucomi(x, y);
+        let (le_abi, ge_abi) = if un {
+            (1, -1)
+        } else if lt {
+            (-1, -1)
+        } else if eq {
+            (0, 0)
+        } else {
+            (1, 1)
+        };
+        let cases: [(unsafe extern "C" fn(), i32); 7] = [
+            (__eqxf2::__eqxf2, le_abi),
+            (__nexf2::__nexf2, le_abi),
+            (__ltxf2::__ltxf2, le_abi),
+            (__lexf2::__lexf2, le_abi),
+            (__gtxf2::__gtxf2, ge_abi),
+            (__gexf2::__gexf2, ge_abi),
+            (__unordxf2::__unordxf2, un as i32),
+        ];
+        for &(f, expected) in cases.iter() {
+            let res = call_int(f, &[x, y]) as i32;
+            if res != expected {
+                panic!(
+                    "{:p}({:?}, {:?}): expected {}, builtins: {}",
+                    f, x, y, expected, res
+                );
+            }
+        }
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_int_to_float() {
+    fuzz(N, |i: i64| {
+        let res = call_xf(__floatdixf::__floatdixf, &[], i as u64, 0, 0);
+        assert_eq!(res.to_bits(), fild(i).to_bits(), "__floatdixf({})", i);
+    });
+    fuzz(N, |i: u64| {
+        let res = call_xf(__floatundixf::__floatundixf, &[], i, 0, 0);
+        assert_eq!(res.to_bits(), from_u64(i).to_bits(), "__floatundixf({})", i);
+    });
+    // The high half is exact, so the sum is rounded once.
+    fuzz(N, |i: i128| {
+        let hi = x87!("fmul", fild((i >> 64) as i64), TWO64);
+        let expected = x87!("fadd", hi, from_u64(i as u64));
+        let res = call_xf(__floattixf::__floattixf, &[], i as u64, (i >> 64) as u64, 0);
+        assert_eq!(res.to_bits(), expected.to_bits(), "__floattixf({})", i);
+    });
+    fuzz(N, |i: u128| {
+        let hi = x87!("fmul", from_u64((i >> 64) as u64), TWO64);
+        let expected = x87!("fadd", hi, from_u64(i as u64));
+        let res = call_xf(
+            __floatuntixf::__floatuntixf,
+            &[],
+            i as u64,
+            (i >> 64) as u64,
+            0,
+        );
+        assert_eq!(res.to_bits(), expected.to_bits(), "__floatuntixf({})", i);
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// Returns the integer part of the magnitude of `x`, or `None` if it does not fit in a `u128`
+fn int_part(x: f80) -> Option<u128> {
+    let exponent = (x.to_bits() >> 64) as i32 & 0x7fff;
+    if x.is_nan() || exponent == 0x7fff {
+        return None;
+    }
+    let significand = x.to_bits() as u64 as u128;
+    let shift = exponent - 16383 - 63;
+    if shift > 64 {
+        None
+    } else if shift >= 0 {
+        Some(significand << shift)
+    } else if shift > -64 {
+        Some(significand >> -shift)
+    } else {
+        Some(0)
+    }
+}
+
+macro_rules! float_to_int {
+    ($($fn:ident, $ty:ident;)*) => {
+        $(
+            fuzz_float(N, |x: f80| {
+                let negative = x.to_bits() >> 79 != 0;
+                let min_abs = (<$ty>::MIN as i128).unsigned_abs();
+                let expected = match int_part(x) {
+                    _ if x.is_nan() => 0,
+                    None if negative => <$ty>::MIN,
+                    None => <$ty>::MAX,
+                    Some(i) if negative && i > min_abs => <$ty>::MIN,
+                    Some(i) if negative => (i as $ty).wrapping_neg(),
+                    Some(i) if i > <$ty>::MAX as u128 => <$ty>::MAX,
+                    Some(i) => i as $ty,
+                };
+                let res = call_int($fn::$fn, &[x]) as $ty;
+                if res != expected {
+                    panic!(
+                        "{}({:?}): expected {}, builtins: {}",
+                        stringify!($fn),
+                        x,
+                        expected,
+                        res
+                    );
+                }
+            });
+        )*
+    };
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_float_to_int() {
+    float_to_int!(
+        __fixxfdi, i64;
+        __fixxfti, i128;
+        __fixunsxfsi, u32;
+        __fixunsxfdi, u64;
+        __fixunsxfti, u128;
+    );
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_tf_conv() {
+    fuzz_float(N, |x: f80| {
+        let res0 = unsafe { call(gcc_extendxftf2, &[x], 0, 0, 0, 0) }.xmm0;
+        let res1 = unsafe { call(__extendxftf2::__extendxftf2, &[x], 0, 0, 0, 0) }.xmm0;
+        let (res0, res1) = (f128::from_bits(res0), f128::from_bits(res1));
+        if !Float::eq_repr(res0, res1) {
+            panic!(
+                "__extendxftf2({:?}): libgcc: {:?}, builtins: {:?}",
+                x, res0, res1
+            );
+        }
+    });
+    fuzz_float(N, |x: f128| {
+        let res0 = call_xf(gcc_trunctfxf2, &[], 0, 0, x.to_bits());
+        let res1 = call_xf(__trunctfxf2::__trunctfxf2, &[], 0, 0, x.to_bits());
+        if !same(res0, res1) {
+            panic!(
+                "__trunctfxf2({:?}): libgcc: {:?}, builtins: {:?}",
+                x, res0, res1
+            );
+        }
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_powi() {
+    fuzz_float_2(N, |x: f80, y: f80| {
+        // Use the bits of `y` as a small exponent.
+        let n = (y.to_bits() as i32) % 300;
+        let res0 = call_xf(gcc_powixf2, &[x], n as u64, 0, 0);
+        let res1 = call_xf(__powixf2::__powixf2, &[x], n as u64, 0, 0);
+        if !same(res0, res1) {
+            panic!(
+                "__powixf2({:?}, {}): libgcc: {:?}, builtins: {:?}",
+                x, n, res0, res1
+            );
+        }
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// Calls the intrinsic `f`, which returns a `_Complex long double`
+fn call_xc(f: unsafe extern "C" fn(), args: &[f80; 4]) -> Complex<f80> {
+    let st = unsafe { call(f, args, 0, 0, 0, 2) }.st;
+    Complex {
+        re: st[0],
+        im: st[1],
+    }
+}
+
+#[cfg(not(feature = "no-asm"))]
+/// The divisors that every fuzzed dividend (and multiplier) is combined with
+fn operands(x: f80, y: f80) -> [(f80, f80); 8] {
+    let inf = f80::from_bits(0x7fff_8000_0000_0000_0000);
+    let nan = f80::from_bits(0x7fff_c000_0000_0000_0000);
+    [
+        (y, x),
+        (x, -y),
+        (f80::ZERO, f80::ZERO),
+        (f80::ONE, f80::ZERO),
+        (inf, f80::ZERO),
+        (f80::ONE, -inf),
+        (nan, f80::ONE),
+        (f80::from_bits(1), f80::ONE),
+    ]
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_mul_complex() {
+    fuzz_float_2(N, |x: f80, y: f80| {
+        for &(c, d) in operands(x, y).iter() {
+            let res0 = call_xc(gcc_mulxc3, &[x, y, c, d]);
+            let res1 = call_xc(__mulxc3::__mulxc3, &[x, y, c, d]);
+            if !same(res0.re, res1.re) || !same(res0.im, res1.im) {
+                panic!(
+                    "__mulxc3({:?}, {:?}, {:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                    x, y, c, d, res0, res1
+                );
+            }
+        }
+    });
+}
+
+#[cfg(not(feature = "no-asm"))]
+#[test]
+fn xf_div_complex() {
+    // 2^-60, for a tolerance of 8 ulps
+    let ulps = f80::from_bits(0x3fc3_8000_0000_0000_0000);
+    let abs = |x: f80| f80::from_bits(x.to_bits() & !(1 << 79));
+    let finite = |x: f80| (x.to_bits() >> 64) as u16 & 0x7fff != 0x7fff;
+    fuzz_float_2(N, |x: f80, y: f80| {
+        for &(c, d) in operands(x, y).iter() {
+            let res0 = call_xc(gcc_divxc3, &[x, y, c, d]);
+            let res1 = call_xc(__divxc3::__divxc3, &[x, y, c, d]);
+            // libgcc uses a different algorithm, so finite results are only required to be
+            // within a few ulps of the larger part. Non-finite results have to match.
+            let (re, im) = (abs(res0.re), abs(res0.im));
+            let scale = if fucomi(re, im).0 { im } else { re };
+            let tolerance = x87!("fmul", scale, ulps);
+            // The algorithm of compiler-rt only scales the divisor, so its intermediate products
+            // can overflow or lose bits to underflow if the dividend is close to the limits of
+            // the exponent range. libgcc scales the dividend too.
+            let exponent = |x: f80| (x.to_bits() >> 64) as u16 & 0x7fff;
+            let extreme = |x: f80| finite(x) && (exponent(x) < 64 || exponent(x) >= 0x7ffd);
+            if extreme(x) || extreme(y) {
+                continue;
+            }
+            let close = |x: f80, y: f80| {
+                if finite(x) && finite(y) {
+                    let diff = abs(x87!("fsub", x, y));
+                    let (lt, eq, _) = fucomi(diff, tolerance);
+                    lt || eq || (diff.to_bits() >> 64) as u16 & 0x7fff == 0
+                } else {
+                    same(x, y)
+                }
+            };
+            if !close(res0.re, res1.re) || !close(res0.im, res1.im) {
+                panic!(
+                    "__divxc3({:?}, {:?}, {:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                    x, y, c, d, res0, res1
+                );
+            }
+        }
+    });
+}
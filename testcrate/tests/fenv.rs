@@ -0,0 +1,246 @@
+#![cfg(feature = "fenv")]
+
+use std::sync::Mutex;
+
+use compiler_builtins::float::add::__addsf3;
+use compiler_builtins::float::conv::{
+    __fixdfsi, __fixsfsi, __fixunssfsi, __floatsisf, __floatuntisf,
+};
+use compiler_builtins::float::div::__divsf3;
+use compiler_builtins::float::extend::__extendsfdf2;
+use compiler_builtins::float::fenv::*;
+use compiler_builtins::float::fma::soft_fma;
+use compiler_builtins::float::mul::__mulsf3;
+use compiler_builtins::float::sqrt::soft_sqrt;
+use compiler_builtins::float::sub::__subsf3;
+use compiler_builtins::float::trunc::__truncdfsf2;
+use testcrate::*;
+
+// The flags are global, so the tests must not run concurrently.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Returns the flags raised by `f`
+fn raised<T>(f: impl FnOnce() -> T) -> u32 {
+    feclearexcept(FE_ALL_EXCEPT);
+    f();
+    fetestexcept(FE_ALL_EXCEPT)
+}
+
+#[test]
+fn flags() {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    feclearexcept(FE_ALL_EXCEPT);
+    feraiseexcept(FE_OVERFLOW | FE_INEXACT);
+    assert_eq!(fetestexcept(FE_ALL_EXCEPT), FE_OVERFLOW | FE_INEXACT);
+    assert_eq!(fetestexcept(FE_INEXACT | FE_INVALID), FE_INEXACT);
+    feclearexcept(FE_INEXACT);
+    assert_eq!(fetestexcept(FE_ALL_EXCEPT), FE_OVERFLOW);
+    // The flags are sticky.
+    __addsf3(1.0, 1.0);
+    assert_eq!(fetestexcept(FE_ALL_EXCEPT), FE_OVERFLOW);
+
+    assert_eq!(
+        raised(|| compiler_builtins::float::fenv::__fe_raise_inexact()),
+        FE_INEXACT
+    );
+    assert_eq!(compiler_builtins::float::fenv::__fe_getround(), 0);
+}
+
+#[test]
+fn exceptions() {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let snan = f32::from_bits(0x7f80_0001);
+    let tiny = f32::from_bits(1);
+    let cases: [(&str, u32, u32); 35] = [
+        ("1 + 1", raised(|| __addsf3(1.0, 1.0)), 0),
+        (
+            "1 + 2^-30",
+            raised(|| __addsf3(1.0, 1.0 / (1u32 << 30) as f32)),
+            FE_INEXACT,
+        ),
+        (
+            "max + max",
+            raised(|| __addsf3(f32::MAX, f32::MAX)),
+            FE_OVERFLOW | FE_INEXACT,
+        ),
+        (
+            "inf - inf",
+            raised(|| __subsf3(f32::INFINITY, f32::INFINITY)),
+            FE_INVALID,
+        ),
+        ("snan + 1", raised(|| __addsf3(snan, 1.0)), FE_INVALID),
+        ("1 + snan", raised(|| __addsf3(1.0, snan)), FE_INVALID),
+        ("nan + 1", raised(|| __addsf3(f32::NAN, 1.0)), 0),
+        (
+            "0 * inf",
+            raised(|| __mulsf3(0.0, f32::INFINITY)),
+            FE_INVALID,
+        ),
+        ("min * 0.5", raised(|| __mulsf3(f32::MIN_POSITIVE, 0.5)), 0),
+        (
+            "tiny * 0.5",
+            raised(|| __mulsf3(tiny, 0.5)),
+            FE_UNDERFLOW | FE_INEXACT,
+        ),
+        (
+            "tiny * tiny",
+            raised(|| __mulsf3(tiny, tiny)),
+            FE_UNDERFLOW | FE_INEXACT,
+        ),
+        (
+            "max * 2",
+            raised(|| __mulsf3(f32::MAX, 2.0)),
+            FE_OVERFLOW | FE_INEXACT,
+        ),
+        ("1 / 0", raised(|| __divsf3(1.0, 0.0)), FE_DIVBYZERO),
+        ("0 / 0", raised(|| __divsf3(0.0, 0.0)), FE_INVALID),
+        ("inf / 0", raised(|| __divsf3(f32::INFINITY, 0.0)), 0),
+        ("1 / 3", raised(|| __divsf3(1.0, 3.0)), FE_INEXACT),
+        ("1.5 as i32", raised(|| __fixsfsi(1.5)), FE_INEXACT),
+        ("3e9 as i32", raised(|| __fixsfsi(3e9)), FE_INVALID),
+        ("nan as i32", raised(|| __fixsfsi(f32::NAN)), FE_INVALID),
+        ("-2^31 as i32", raised(|| __fixsfsi(-2147483648.0)), 0),
+        (
+            "-2^31 - 0.5 as i32",
+            raised(|| __fixdfsi(-2147483648.5)),
+            FE_INEXACT,
+        ),
+        (
+            "-2^31 - 1 as i32",
+            raised(|| __fixdfsi(-2147483649.0)),
+            FE_INVALID,
+        ),
+        ("-1 as u32", raised(|| __fixunssfsi(-1.0)), FE_INVALID),
+        ("-0.5 as u32", raised(|| __fixunssfsi(-0.5)), FE_INEXACT),
+        (
+            "(2^24 + 1) as f32",
+            raised(|| __floatsisf(16777217)),
+            FE_INEXACT,
+        ),
+        (
+            "u128::MAX as f32",
+            raised(|| __floatuntisf(u128::MAX)),
+            FE_OVERFLOW | FE_INEXACT,
+        ),
+        (
+            "1e300 as f32",
+            raised(|| __truncdfsf2(1e300)),
+            FE_OVERFLOW | FE_INEXACT,
+        ),
+        (
+            "1e-300 as f32",
+            raised(|| __truncdfsf2(1e-300)),
+            FE_UNDERFLOW | FE_INEXACT,
+        ),
+        ("0.1 as f32", raised(|| __truncdfsf2(0.1)), FE_INEXACT),
+        ("snan as f64", raised(|| __extendsfdf2(snan)), FE_INVALID),
+        ("sqrt(4)", raised(|| soft_sqrt(4.0f32)), 0),
+        ("sqrt(2)", raised(|| soft_sqrt(2.0f32)), FE_INEXACT),
+        ("sqrt(-1)", raised(|| soft_sqrt(-1.0f32)), FE_INVALID),
+        (
+            "inf * 0 + 1",
+            raised(|| soft_fma(f32::INFINITY, 0.0, 1.0)),
+            FE_INVALID,
+        ),
+        // The product is not rounded on its own, so it does not overflow.
+        (
+            "max * 2 - max",
+            raised(|| soft_fma(f32::MAX, 2.0, -f32::MAX)),
+            0,
+        ),
+    ];
+    for &(name, raised, expected) in cases.iter() {
+        assert_eq!(raised, expected, "{}", name);
+    }
+}
+
+#[test]
+fn exceptions_fuzz() {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Only inexact results can signal an underflow or an overflow.
+    let expected = |x: f32, y: f32, res: f32, inexact: bool, tiny: bool| {
+        if x.is_nan() || y.is_nan() || !x.is_finite() || !y.is_finite() || res.is_nan() {
+            return None;
+        }
+        let mut excepts = 0;
+        if inexact || res.is_infinite() {
+            excepts |= FE_INEXACT;
+            if tiny {
+                excepts |= FE_UNDERFLOW;
+            }
+            if res.is_infinite() {
+                excepts |= FE_OVERFLOW;
+            }
+        }
+        Some(excepts)
+    };
+    fuzz_float_2(N, |x: f32, y: f32| {
+        let mut res = 0.0;
+        let add = raised(|| res = __addsf3(x, y));
+        // Fast2Sum gives the rounding error of the sum, which is never tiny when it is not exact.
+        let (a, b) = if x.abs() < y.abs() { (y, x) } else { (x, y) };
+        let inexact = b - (res - a) != 0.0;
+        if let Some(expected) = expected(x, y, res, inexact, false) {
+            assert_eq!(add, expected, "{:?} + {:?}", x, y);
+        }
+
+        // `f64` holds the exact product of two `f32`s.
+        let mul = raised(|| res = __mulsf3(x, y));
+        let exact = x as f64 * y as f64;
+        let tiny = exact.abs() < f32::MIN_POSITIVE as f64;
+        if let Some(expected) = expected(x, y, res, res as f64 != exact, tiny) {
+            assert_eq!(mul, expected, "{:?} * {:?}", x, y);
+        }
+
+        let div = raised(|| res = __divsf3(x, y));
+        if y != 0.0 {
+            // The quotient is exact if it gives back the dividend.
+            let inexact = res as f64 * y as f64 != x as f64;
+            let tiny = (x as f64 / y as f64).abs() < f32::MIN_POSITIVE as f64;
+            if let Some(expected) = expected(x, y, res, inexact, tiny) {
+                assert_eq!(div, expected, "{:?} / {:?}", x, y);
+            }
+        }
+    });
+}
+
+// The hooks follow the hardware floating point environment, which is set through libc here.
+#[cfg(all(target_arch = "x86_64", target_os = "linux", not(feature = "no-asm")))]
+#[test]
+fn hooks_x86_64() {
+    use compiler_builtins::float::fenv::{__fe_getround, __fe_raise_inexact};
+
+    // The values of glibc
+    const FE_TONEAREST: i32 = 0;
+    const FE_DOWNWARD: i32 = 0x400;
+    const FE_UPWARD: i32 = 0x800;
+    const FE_TOWARDZERO: i32 = 0xc00;
+    const FE_INEXACT: i32 = 0x20;
+
+    extern "C" {
+        fn fesetround(round: i32) -> i32;
+        fn feclearexcept(excepts: i32) -> i32;
+        fn fetestexcept(excepts: i32) -> i32;
+    }
+
+    let modes = [
+        (FE_DOWNWARD, 1),
+        (FE_UPWARD, 2),
+        (FE_TOWARDZERO, 3),
+        (FE_TONEAREST, 0),
+    ];
+    for &(mode, expected) in modes.iter() {
+        unsafe { assert_eq!(fesetround(mode), 0) };
+        assert_eq!(__fe_getround(), expected, "{:#x}", mode);
+    }
+
+    unsafe {
+        feclearexcept(FE_INEXACT);
+        __fe_raise_inexact();
+        assert_eq!(fetestexcept(FE_INEXACT), FE_INEXACT);
+        feclearexcept(FE_INEXACT);
+    }
+}
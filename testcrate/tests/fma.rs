@@ -0,0 +1,56 @@
+use compiler_builtins::float::fma::soft_fma;
+use compiler_builtins::float::{Float, SoftF32, SoftF64};
+use testcrate::*;
+
+macro_rules! fma {
+    ($($fX:ident),*) => {
+        $(
+            fuzz_float_2(N, |x: $fX, y: $fX| {
+                // Addends that cancel the product or are far away from it are the interesting ones.
+                let product = x * y;
+                let addends = [
+                    -product,
+                    product,
+                    x,
+                    -y,
+                    1.0,
+                    <$fX>::MIN_POSITIVE,
+                    <$fX>::from_bits(1),
+                    <$fX>::MAX,
+                    <$fX>::INFINITY,
+                    -0.0,
+                    <$fX>::NAN,
+                ];
+                for &z in addends.iter() {
+                    let fma0 = x.mul_add(y, z);
+                    let fma1: $fX = soft_fma(x, y, z);
+                    if !(Float::eq_repr(fma0, fma1) || (fma0.is_nan() && fma1.is_nan())) {
+                        panic!(
+                            "fma({:?}, {:?}, {:?}): std: {:?}, builtins: {:?}",
+                            x, y, z, fma0, fma1
+                        );
+                    }
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn float_fma() {
+    fma!(f32, f64);
+}
+
+#[test]
+fn soft_mul_add() {
+    let third = SoftF64::from_i32(1) / SoftF64::from_i32(3);
+    // The rounding error of `1/3` is only visible without the intermediate rounding.
+    let error = third.mul_add(SoftF64::from_i32(3), SoftF64::from_i32(-1));
+    assert_eq!(f64::from(error), (1.0f64 / 3.0).mul_add(3.0, -1.0));
+    assert!(f64::from(error) != 0.0);
+
+    let x = SoftF32::from(1.0f32 + f32::EPSILON);
+    let y = SoftF32::from(1.0f32 - f32::EPSILON);
+    let res = x.mul_add(y, SoftF32::from(-1.0f32));
+    assert_eq!(f32::from(res), -f32::EPSILON * f32::EPSILON);
+}
@@ -93,6 +93,98 @@ fn leading_zeros() {
     })
 }
 
+macro_rules! bits_exhaustive {
+    ($($ty:ty),*) => {
+        $(
+            for x in 0..=<$ty>::MAX {
+                let tz = x.trailing_zeros();
+                let ones = x.count_ones();
+                assert_eq!(trailing_zeros_default(x), tz, "trailing_zeros_default({})", x);
+                assert_eq!(trailing_zeros_riscv(x), tz, "trailing_zeros_riscv({})", x);
+                assert_eq!(count_ones_default(x), ones, "count_ones_default({})", x);
+                assert_eq!(count_ones_no_mul(x), ones, "count_ones_no_mul({})", x);
+                assert_eq!(parity(x), ones & 1, "parity({})", x);
+            }
+        )*
+    };
+}
+
+#[test]
+fn bits() {
+    use compiler_builtins::int::bits::*;
+
+    bits_exhaustive!(u8, u16);
+
+    fuzz(N, |x: u32| {
+        let ffs = if x == 0 {
+            0
+        } else {
+            x.trailing_zeros() as i32 + 1
+        };
+        let i = x as i32;
+        assert_eq!(
+            __popcountsi2(i),
+            x.count_ones() as i32,
+            "__popcountsi2({})",
+            x
+        );
+        assert_eq!(
+            __paritysi2(i),
+            (x.count_ones() & 1) as i32,
+            "__paritysi2({})",
+            x
+        );
+        assert_eq!(__ctzsi2(i), x.trailing_zeros() as i32, "__ctzsi2({})", x);
+        assert_eq!(__ffssi2(i), ffs, "__ffssi2({})", x);
+        assert_eq!(__bswapsi2(x), x.swap_bytes(), "__bswapsi2({})", x);
+    });
+    fuzz(N, |x: u64| {
+        let ffs = if x == 0 {
+            0
+        } else {
+            x.trailing_zeros() as i32 + 1
+        };
+        let i = x as i64;
+        assert_eq!(
+            __popcountdi2(i),
+            x.count_ones() as i32,
+            "__popcountdi2({})",
+            x
+        );
+        assert_eq!(
+            __paritydi2(i),
+            (x.count_ones() & 1) as i32,
+            "__paritydi2({})",
+            x
+        );
+        assert_eq!(__ctzdi2(i), x.trailing_zeros() as i32, "__ctzdi2({})", x);
+        assert_eq!(__ffsdi2(i), ffs, "__ffsdi2({})", x);
+        assert_eq!(__bswapdi2(x), x.swap_bytes(), "__bswapdi2({})", x);
+    });
+    fuzz(N, |x: u128| {
+        let ffs = if x == 0 {
+            0
+        } else {
+            x.trailing_zeros() as i32 + 1
+        };
+        let i = x as i128;
+        assert_eq!(
+            __popcountti2(i),
+            x.count_ones() as i32,
+            "__popcountti2({})",
+            x
+        );
+        assert_eq!(
+            __parityti2(i),
+            (x.count_ones() & 1) as i32,
+            "__parityti2({})",
+            x
+        );
+        assert_eq!(__ctzti2(i), x.trailing_zeros() as i32, "__ctzti2({})", x);
+        assert_eq!(__ffsti2(i), ffs, "__ffsti2({})", x);
+    });
+}
+
 macro_rules! extend {
     ($fX:ident, $fD:ident, $fn:ident) => {
         fuzz_float(N, |x: $fX| {
@@ -126,6 +218,222 @@ fn float_extend_arm() {
     extend!(f32, f64, __extendsfdf2vfp);
 }
 
+macro_rules! trunc {
+    ($fX:ident, $fD:ident, $fn:ident) => {
+        fuzz_float(N, |x: $fX| {
+            let tmp0 = x as $fD;
+            let tmp1: $fD = $fn(x);
+            if !Float::eq_repr(tmp0, tmp1) {
+                panic!(
+                    "{}({}): std: {}, builtins: {}",
+                    stringify!($fn),
+                    x,
+                    tmp0,
+                    tmp1
+                );
+            }
+        });
+    };
+}
+
+#[test]
+fn float_trunc() {
+    use compiler_builtins::float::trunc::__truncdfsf2;
+
+    trunc!(f64, f32, __truncdfsf2);
+}
+
+#[cfg(target_arch = "arm")]
+#[test]
+fn float_trunc_arm() {
+    use compiler_builtins::float::trunc::__truncdfsf2vfp;
+
+    trunc!(f64, f32, __truncdfsf2vfp);
+}
+
+/// The value of a binary16 number, computed without going through any of the conversions under
+/// test. Every binary16 value is exactly representable as `f32`.
+fn f16_to_f32_reference(x: u16) -> f32 {
+    let sign = ((x as u32) & 0x8000) << 16;
+    let exp = (x >> 10) & 0x1f;
+    let frac = x & 0x3ff;
+    let abs = if exp == 0x1f {
+        // infinity or NaN, the payload is kept as is
+        return f32::from_bits(sign | 0x7f80_0000 | ((frac as u32) << 13));
+    } else if exp == 0 {
+        frac as f32 * 2f32.powi(-24)
+    } else {
+        (frac | 0x400) as f32 * 2f32.powi(exp as i32 - 25)
+    };
+    f32::from_bits(sign | abs.to_bits())
+}
+
+#[test]
+fn float_extend_f16() {
+    use compiler_builtins::float::extend::{__extendhfsf2, __gnu_h2f_ieee};
+    use compiler_builtins::float::f16;
+
+    for x in 0..=u16::MAX {
+        let tmp0 = f16_to_f32_reference(x);
+        for (name, tmp1) in &[
+            ("__extendhfsf2", __extendhfsf2(f16::from_bits(x))),
+            ("__gnu_h2f_ieee", __gnu_h2f_ieee(f16::from_bits(x))),
+        ] {
+            if tmp0.to_bits() != tmp1.to_bits() {
+                panic!(
+                    "{}({:#06x}): expected: {:#010x}, builtins: {:#010x}",
+                    name,
+                    x,
+                    tmp0.to_bits(),
+                    tmp1.to_bits()
+                );
+            }
+        }
+    }
+}
+
+macro_rules! trunc_f16 {
+    ($fX:ident, $($fn:ident),*) => {
+        // Every binary16 value has to be preserved, and values halfway between two consecutive
+        // binary16 values have to round to the even one.
+        for x in 0..=u16::MAX {
+            let f = f16_to_f32_reference(x) as $fX;
+            let next = f16_to_f32_reference(x.wrapping_add(1)) as $fX;
+            $(
+                let res = $fn(f).to_bits();
+                if f.is_nan() {
+                    if res & 0x7fff <= 0x7c00 || res & 0x8000 != x & 0x8000 {
+                        panic!("{}({:#06x}): builtins: {:#06x}", stringify!($fn), x, res);
+                    }
+                } else if res != x {
+                    panic!("{}({:#06x}): builtins: {:#06x}", stringify!($fn), x, res);
+                }
+                // `f16::MAX` rounds up to infinity, which is checked below
+                if x & 0x7fff < 0x7bff {
+                    let mid = (f + next) / 2.0;
+                    let below = <$fX>::from_bits(mid.to_bits() - 1);
+                    let above = <$fX>::from_bits(mid.to_bits() + 1);
+                    let even = if x & 1 == 0 { x } else { x + 1 };
+                    for &(y, expected) in &[(below, x), (mid, even), (above, x + 1)] {
+                        let res = $fn(y).to_bits();
+                        if res != expected {
+                            panic!(
+                                "{}({:?}): expected: {:#06x}, builtins: {:#06x}",
+                                stringify!($fn),
+                                y,
+                                expected,
+                                res
+                            );
+                        }
+                    }
+                }
+            )*
+        }
+        // 65520 is halfway between `f16::MAX` and the next value the exponent range would allow
+        let overflow: $fX = 65520.0;
+        let below = <$fX>::from_bits(overflow.to_bits() - 1);
+        $(
+            for &(y, expected) in &[(below, 0x7bff), (overflow, 0x7c00), (-below, 0xfbff)] {
+                let res = $fn(y).to_bits();
+                if res != expected {
+                    panic!(
+                        "{}({:?}): expected: {:#06x}, builtins: {:#06x}",
+                        stringify!($fn),
+                        y,
+                        expected,
+                        res
+                    );
+                }
+            }
+        )*
+    };
+}
+
+#[test]
+fn float_trunc_f16() {
+    use compiler_builtins::float::trunc::{__gnu_f2h_ieee, __truncdfhf2, __truncsfhf2};
+
+    trunc_f16!(f32, __truncsfhf2, __gnu_f2h_ieee);
+    trunc_f16!(f64, __truncdfhf2);
+}
+
+#[test]
+fn float_extend_bf16() {
+    use compiler_builtins::float::bf16;
+    use compiler_builtins::float::extend::__extendbfsf2;
+
+    // bfloat16 is the upper half of binary32, NaN payloads included.
+    for x in 0..=u16::MAX {
+        let res = __extendbfsf2(bf16::from_bits(x)).to_bits();
+        if res != (x as u32) << 16 {
+            panic!("__extendbfsf2({:#06x}): builtins: {:#010x}", x, res);
+        }
+    }
+}
+
+macro_rules! trunc_bf16 {
+    ($fX:ident, $fn:ident) => {
+        // Every bfloat16 value has to be preserved and NaNs have to be quieted. Values halfway
+        // between two consecutive bfloat16 values have to round to the even one.
+        for x in 0..=u16::MAX {
+            let f = f32::from_bits((x as u32) << 16) as $fX;
+            let res = $fn(f).to_bits();
+            if f.is_nan() {
+                if res & 0x7fc0 != 0x7fc0 || res & 0x8000 != x & 0x8000 {
+                    panic!("{}({:#06x}): builtins: {:#06x}", stringify!($fn), x, res);
+                }
+            } else if res != x {
+                panic!("{}({:#06x}): builtins: {:#06x}", stringify!($fn), x, res);
+            }
+            // The largest finite value rounds up to infinity, which is checked below
+            if x & 0x7fff < 0x7f7f {
+                let next = f32::from_bits((x as u32 + 1) << 16) as $fX;
+                // `f + next` can overflow in `f32`
+                let mid = f + (next - f) / 2.0;
+                let below = <$fX>::from_bits(mid.to_bits() - 1);
+                let above = <$fX>::from_bits(mid.to_bits() + 1);
+                let even = if x & 1 == 0 { x } else { x + 1 };
+                for &(y, expected) in &[(below, x), (mid, even), (above, x + 1)] {
+                    let res = $fn(y).to_bits();
+                    if res != expected {
+                        panic!(
+                            "{}({:?}): expected: {:#06x}, builtins: {:#06x}",
+                            stringify!($fn),
+                            y,
+                            expected,
+                            res
+                        );
+                    }
+                }
+            }
+        }
+        // Halfway between the largest finite value and the next value the exponent range would
+        // allow
+        let overflow = f32::from_bits(0x7f7f_8000) as $fX;
+        let below = <$fX>::from_bits(overflow.to_bits() - 1);
+        for &(y, expected) in &[(below, 0x7f7f), (overflow, 0x7f80), (-below, 0xff7f)] {
+            let res = $fn(y).to_bits();
+            if res != expected {
+                panic!(
+                    "{}({:?}): expected: {:#06x}, builtins: {:#06x}",
+                    stringify!($fn),
+                    y,
+                    expected,
+                    res
+                );
+            }
+        }
+    };
+}
+
+#[test]
+fn float_trunc_bf16() {
+    use compiler_builtins::float::trunc::{__truncdfbf2, __truncsfbf2};
+
+    trunc_bf16!(f32, __truncsfbf2);
+    trunc_bf16!(f64, __truncdfbf2);
+}
+
 // This is approximate because of issues related to
 // https://github.com/rust-lang/rust/issues/73920.
 // TODO how do we resolve this indeterminacy?
@@ -177,3 +485,91 @@ fn float_pow() {
         f64, 1e-12, __powidf2;
     );
 }
+
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    all(
+        any(target_arch = "aarch64", target_arch = "arm"),
+        target_os = "linux",
+        not(feature = "no-asm")
+    )
+))]
+#[test]
+fn clear_cache() {
+    use compiler_builtins::clear_cache::__clear_cache;
+
+    // Spans several cache lines, and is cleared from unaligned boundaries as well
+    let mut buf: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+    let range = buf.as_mut_ptr_range();
+    __clear_cache(range.start, range.end);
+    __clear_cache(buf[3..1021].as_mut_ptr(), buf[1021..].as_mut_ptr());
+    __clear_cache(range.start, range.start);
+    assert!(buf.iter().enumerate().all(|(i, &x)| x == i as u8));
+}
+
+// Runs code that is rewritten in place, which only works if `__clear_cache` makes the new
+// instructions visible
+#[cfg(all(
+    any(
+        target_arch = "x86_64",
+        all(
+            any(target_arch = "aarch64", target_arch = "riscv64"),
+            not(feature = "no-asm")
+        )
+    ),
+    target_os = "linux"
+))]
+#[test]
+fn clear_cache_exec() {
+    use compiler_builtins::clear_cache::__clear_cache;
+
+    extern "C" {
+        fn mmap(addr: *mut u8, len: usize, prot: i32, flags: i32, fd: i32, offset: i64) -> *mut u8;
+        fn munmap(addr: *mut u8, len: usize) -> i32;
+    }
+    const PROT_READ_WRITE_EXEC: i32 = 7;
+    const MAP_PRIVATE_ANONYMOUS: i32 = 0x22;
+    const LEN: usize = 4096;
+
+    /// Returns the machine code of a function that returns `x`, which must fit in 11 bits
+    fn stub(x: u16) -> Vec<u8> {
+        if cfg!(target_arch = "x86_64") {
+            // mov eax, x; ret
+            let mut code = vec![0xb8];
+            code.extend_from_slice(&(x as u32).to_le_bytes());
+            code.push(0xc3);
+            code
+        } else if cfg!(target_arch = "aarch64") {
+            // movz w0, #x; ret
+            let mut code = (0x5280_0000 | (x as u32) << 5).to_le_bytes().to_vec();
+            code.extend_from_slice(&0xd65f_03c0u32.to_le_bytes());
+            code
+        } else {
+            // addi a0, zero, x; ret
+            let mut code = (0x0000_0513 | (x as u32) << 20).to_le_bytes().to_vec();
+            code.extend_from_slice(&0x0000_8067u32.to_le_bytes());
+            code
+        }
+    }
+
+    unsafe {
+        let code = mmap(
+            core::ptr::null_mut(),
+            LEN,
+            PROT_READ_WRITE_EXEC,
+            MAP_PRIVATE_ANONYMOUS,
+            -1,
+            0,
+        );
+        assert_ne!(code as isize, -1, "mmap failed");
+        let f: extern "C" fn() -> u32 = core::mem::transmute(code);
+        for &x in [1u16, 2, 0x7ff].iter() {
+            let stub = stub(x);
+            core::ptr::copy_nonoverlapping(stub.as_ptr(), code, stub.len());
+            __clear_cache(code, code.add(stub.len()));
+            assert_eq!(f(), x as u32);
+        }
+        assert_eq!(munmap(code, LEN), 0);
+    }
+}
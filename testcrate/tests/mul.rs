@@ -1,4 +1,5 @@
 #![allow(unused_macros)]
+#![feature(simd_ffi)]
 
 use testcrate::*;
 
@@ -21,9 +22,10 @@ macro_rules! mul {
 
 #[test]
 fn mul() {
-    use compiler_builtins::int::mul::{__muldi3, __multi3};
+    use compiler_builtins::int::mul::{__muldi3, __mulhi3, __multi3};
 
     mul!(
+        i16, __mulhi3;
         u64, __muldi3;
         i128, __multi3;
     );
@@ -87,14 +89,11 @@ macro_rules! float_mul {
             fuzz_float_2(N, |x: $f, y: $f| {
                 let mul0 = x * y;
                 let mul1: $f = $fn(x, y);
-                // multiplication of subnormals is not currently handled
-                if !(Float::is_subnormal(mul0) || Float::is_subnormal(mul1)) {
-                    if !Float::eq_repr(mul0, mul1) {
-                        panic!(
-                            "{}({}, {}): std: {}, builtins: {}",
-                            stringify!($fn), x, y, mul0, mul1
-                        );
-                    }
+                if !Float::eq_repr(mul0, mul1) {
+                    panic!(
+                        "{}({}, {}): std: {}, builtins: {}",
+                        stringify!($fn), x, y, mul0, mul1
+                    );
                 }
             });
         )*
@@ -128,3 +127,36 @@ fn float_mul_arm() {
         f64, __muldf3vfp;
     );
 }
+
+// There is no native `f128` to compare against, so the libgcc implementation is used as the
+// reference.
+#[cfg(all(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "mips64"
+    ),
+    target_os = "linux"
+))]
+#[test]
+fn float_mul_f128() {
+    use compiler_builtins::float::{f128, mul::__multf3, Float};
+
+    // `f128` is passed like `__float128`, even though the lint cannot tell.
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "__multf3"]
+        fn gcc_multf3(a: f128, b: f128) -> f128;
+    }
+
+    fuzz_float_2(N, |x: f128, y: f128| {
+        let mul0 = unsafe { gcc_multf3(x, y) };
+        let mul1 = __multf3(x, y);
+        if !Float::eq_repr(mul0, mul1) {
+            panic!(
+                "__multf3({:?}, {:?}): libgcc: {:?}, builtins: {:?}",
+                x, y, mul0, mul1
+            );
+        }
+    });
+}
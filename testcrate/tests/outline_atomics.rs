@@ -0,0 +1,171 @@
+// Which of the LSE and LL/SC paths these tests cover depends on the CPU, so CI runs them under
+// `qemu-aarch64` both with and without LSE.
+#![cfg(all(
+    target_arch = "aarch64",
+    any(target_os = "linux", target_os = "android"),
+    not(feature = "no-asm")
+))]
+
+use std::mem;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use compiler_builtins::aarch64::*;
+use testcrate::*;
+
+/// The helpers are naked functions, so they are given their C signature here
+macro_rules! helper {
+    ($name:ident: $($sig:tt)*) => {
+        mem::transmute::<unsafe extern "C" fn(), unsafe extern "C" fn$($sig)*>($name)
+    };
+}
+
+macro_rules! cas {
+    ($($ty:ty: $($name:ident),*;)*) => {
+        $($(
+            fuzz_2(N, |x: $ty, y: $ty| unsafe {
+                let f = helper!($name: ($ty, $ty, *mut $ty) -> $ty);
+                let mut v = x;
+                assert_eq!(f(x, y, &mut v), x, "{}({}, {})", stringify!($name), x, y);
+                assert_eq!(v, y, "{}({}, {})", stringify!($name), x, y);
+                if x != y {
+                    assert_eq!(f(x, !y, &mut v), y, "{}({}, {})", stringify!($name), x, !y);
+                    assert_eq!(v, y, "{}({}, {})", stringify!($name), x, !y);
+                }
+            });
+        )*)*
+    };
+}
+
+macro_rules! ldop {
+    ($($ty:ty, $op:expr => $($name:ident),*;)*) => {
+        $($(
+            fuzz_2(N, |x: $ty, y: $ty| unsafe {
+                let f = helper!($name: ($ty, *mut $ty) -> $ty);
+                let mut v = x;
+                assert_eq!(f(y, &mut v), x, "{}({}, {})", stringify!($name), y, x);
+                assert_eq!(v, $op(x, y), "{}({}, {})", stringify!($name), y, x);
+            });
+        )*)*
+    };
+}
+
+#[test]
+fn outline_atomics_cas() {
+    cas!(
+        u8: __aarch64_cas1_relax, __aarch64_cas1_acq, __aarch64_cas1_rel, __aarch64_cas1_acq_rel;
+        u16: __aarch64_cas2_relax, __aarch64_cas2_acq, __aarch64_cas2_rel, __aarch64_cas2_acq_rel;
+        u32: __aarch64_cas4_relax, __aarch64_cas4_acq, __aarch64_cas4_rel, __aarch64_cas4_acq_rel;
+        u64: __aarch64_cas8_relax, __aarch64_cas8_acq, __aarch64_cas8_rel, __aarch64_cas8_acq_rel;
+        u128: __aarch64_cas16_relax, __aarch64_cas16_acq, __aarch64_cas16_rel,
+            __aarch64_cas16_acq_rel;
+    );
+}
+
+#[test]
+fn outline_atomics_ldop() {
+    ldop!(
+        u8, |_, y| y => __aarch64_swp1_relax, __aarch64_swp1_acq, __aarch64_swp1_rel,
+            __aarch64_swp1_acq_rel;
+        u16, |_, y| y => __aarch64_swp2_relax, __aarch64_swp2_acq, __aarch64_swp2_rel,
+            __aarch64_swp2_acq_rel;
+        u32, |_, y| y => __aarch64_swp4_relax, __aarch64_swp4_acq, __aarch64_swp4_rel,
+            __aarch64_swp4_acq_rel;
+        u64, |_, y| y => __aarch64_swp8_relax, __aarch64_swp8_acq, __aarch64_swp8_rel,
+            __aarch64_swp8_acq_rel;
+        u8, u8::wrapping_add => __aarch64_ldadd1_relax, __aarch64_ldadd1_acq,
+            __aarch64_ldadd1_rel, __aarch64_ldadd1_acq_rel;
+        u16, u16::wrapping_add => __aarch64_ldadd2_relax, __aarch64_ldadd2_acq,
+            __aarch64_ldadd2_rel, __aarch64_ldadd2_acq_rel;
+        u32, u32::wrapping_add => __aarch64_ldadd4_relax, __aarch64_ldadd4_acq,
+            __aarch64_ldadd4_rel, __aarch64_ldadd4_acq_rel;
+        u64, u64::wrapping_add => __aarch64_ldadd8_relax, __aarch64_ldadd8_acq,
+            __aarch64_ldadd8_rel, __aarch64_ldadd8_acq_rel;
+        u8, |x: u8, y: u8| x & !y => __aarch64_ldclr1_relax, __aarch64_ldclr1_acq,
+            __aarch64_ldclr1_rel, __aarch64_ldclr1_acq_rel;
+        u16, |x: u16, y: u16| x & !y => __aarch64_ldclr2_relax, __aarch64_ldclr2_acq,
+            __aarch64_ldclr2_rel, __aarch64_ldclr2_acq_rel;
+        u32, |x: u32, y: u32| x & !y => __aarch64_ldclr4_relax, __aarch64_ldclr4_acq,
+            __aarch64_ldclr4_rel, __aarch64_ldclr4_acq_rel;
+        u64, |x: u64, y: u64| x & !y => __aarch64_ldclr8_relax, __aarch64_ldclr8_acq,
+            __aarch64_ldclr8_rel, __aarch64_ldclr8_acq_rel;
+        u8, |x: u8, y: u8| x ^ y => __aarch64_ldeor1_relax, __aarch64_ldeor1_acq,
+            __aarch64_ldeor1_rel, __aarch64_ldeor1_acq_rel;
+        u16, |x: u16, y: u16| x ^ y => __aarch64_ldeor2_relax, __aarch64_ldeor2_acq,
+            __aarch64_ldeor2_rel, __aarch64_ldeor2_acq_rel;
+        u32, |x: u32, y: u32| x ^ y => __aarch64_ldeor4_relax, __aarch64_ldeor4_acq,
+            __aarch64_ldeor4_rel, __aarch64_ldeor4_acq_rel;
+        u64, |x: u64, y: u64| x ^ y => __aarch64_ldeor8_relax, __aarch64_ldeor8_acq,
+            __aarch64_ldeor8_rel, __aarch64_ldeor8_acq_rel;
+        u8, |x: u8, y: u8| x | y => __aarch64_ldset1_relax, __aarch64_ldset1_acq,
+            __aarch64_ldset1_rel, __aarch64_ldset1_acq_rel;
+        u16, |x: u16, y: u16| x | y => __aarch64_ldset2_relax, __aarch64_ldset2_acq,
+            __aarch64_ldset2_rel, __aarch64_ldset2_acq_rel;
+        u32, |x: u32, y: u32| x | y => __aarch64_ldset4_relax, __aarch64_ldset4_acq,
+            __aarch64_ldset4_rel, __aarch64_ldset4_acq_rel;
+        u64, |x: u64, y: u64| x | y => __aarch64_ldset8_relax, __aarch64_ldset8_acq,
+            __aarch64_ldset8_rel, __aarch64_ldset8_acq_rel;
+    );
+}
+
+// The constructor has to have run before the tests, and agree with the kernel
+#[test]
+fn outline_atomics_lse_detection() {
+    extern "C" {
+        fn getauxval(type_: u64) -> u64;
+    }
+    const AT_HWCAP: u64 = 16;
+    const HWCAP_ATOMICS: u64 = 1 << 8;
+
+    let hwcap = unsafe { getauxval(AT_HWCAP) };
+    assert_eq!(
+        HAVE_LSE_ATOMICS.load(Ordering::Relaxed),
+        hwcap & HWCAP_ATOMICS != 0
+    );
+}
+
+/// A `u128` shared between threads, which is only accessed through the helpers
+struct Shared(*mut u128);
+
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+#[test]
+fn outline_atomics_contention() {
+    const THREADS: usize = 4;
+    const ITERATIONS: u128 = 10_000;
+
+    let shared = Arc::new(Shared(Box::into_raw(Box::new(0u128))));
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let shared = shared.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || unsafe {
+                let cas = helper!(__aarch64_cas16_acq_rel: (u128, u128, *mut u128) -> u128);
+                barrier.wait();
+                let mut old = 0u128;
+                for _ in 0..ITERATIONS {
+                    loop {
+                        let new = old.wrapping_add(1 << 64 | 1);
+                        let cur = cas(old, new, shared.0);
+                        if cur == old {
+                            old = new;
+                            break;
+                        }
+                        old = cur;
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let total = THREADS as u128 * ITERATIONS;
+    unsafe {
+        assert_eq!(*shared.0, total << 64 | total);
+        drop(Box::from_raw(shared.0));
+    }
+}
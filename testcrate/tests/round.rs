@@ -0,0 +1,291 @@
+use core::cmp::Ordering;
+
+use compiler_builtins::float::round::{
+    add_rounded, div_rounded, int_to_float_rounded, mul_rounded, sub_rounded, Round,
+};
+use compiler_builtins::float::Float;
+use compiler_builtins::int::Int;
+use testcrate::*;
+
+const ROUNDS: [Round; 4] = [Round::Nearest, Round::Up, Round::Down, Round::Zero];
+
+/// Returns the float that is expected for a result with the direction `round`, given the result
+/// `n` rounded to nearest by the hardware and how the exact result compares to it.
+fn expected<F: Float>(n: F, exact: Ordering, round: Round) -> F {
+    let next_up = |x: F| {
+        if x.repr() & !F::SIGN_MASK == F::Int::ZERO {
+            F::from_repr(F::Int::ONE)
+        } else if x.sign() {
+            F::from_repr(x.repr() - F::Int::ONE)
+        } else {
+            F::from_repr(x.repr() + F::Int::ONE)
+        }
+    };
+    let next_down = |x: F| {
+        if x.repr() & !F::SIGN_MASK == F::Int::ZERO {
+            F::from_repr(F::SIGN_MASK | F::Int::ONE)
+        } else if x.sign() {
+            F::from_repr(x.repr() + F::Int::ONE)
+        } else {
+            F::from_repr(x.repr() - F::Int::ONE)
+        }
+    };
+    let up = || {
+        if exact == Ordering::Greater {
+            next_up(n)
+        } else {
+            n
+        }
+    };
+    let down = || {
+        if exact == Ordering::Less {
+            next_down(n)
+        } else {
+            n
+        }
+    };
+    match round {
+        Round::Nearest => n,
+        Round::Up => up(),
+        Round::Down => down(),
+        Round::Zero => {
+            let positive = if n.repr() & !F::SIGN_MASK == F::Int::ZERO {
+                exact == Ordering::Greater
+            } else {
+                !n.sign()
+            };
+            if positive {
+                down()
+            } else {
+                up()
+            }
+        }
+    }
+}
+
+fn check<F: Float>(name: &str, x: F, y: F, round: Round, expected: F, res: F) {
+    if !Float::eq_repr(expected, res) {
+        panic!(
+            "{}({:?}, {:?}, {:?}): expected: {:?}, builtins: {:?}",
+            name, x, y, round, expected, res
+        );
+    }
+}
+
+macro_rules! directed {
+    ($fX:ident, $mul_err:expr, $div_err:expr) => {
+        fuzz_float_2(N, |x: $fX, y: $fX| {
+            // `exact` compares the exact result to the one rounded to nearest, `None` if the
+            // operation is not checked for these inputs.
+            let overflowed = |n: $fX| {
+                if n.is_infinite() && x.is_finite() && y.is_finite() {
+                    Some(if n > 0.0 {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    })
+                } else {
+                    None
+                }
+            };
+            // Fast2Sum, which gives the exact rounding error of `x + y` if `|x| >= |y|`
+            let two_sum = |x: $fX, y: $fX| {
+                let (x, y) = if x.abs() < y.abs() { (y, x) } else { (x, y) };
+                let n = x + y;
+                overflowed(n).unwrap_or_else(|| {
+                    let err = y - (n - x);
+                    err.partial_cmp(&0.0).unwrap_or(Ordering::Equal)
+                })
+            };
+
+            let sum = x + y;
+            let exact = two_sum(x, y);
+            for &round in ROUNDS.iter() {
+                let mut expected = expected(sum, exact, round);
+                // An exact zero sum of operands with different signs is -0 when rounding down.
+                if sum == 0.0
+                    && round == Round::Down
+                    && !(x == 0.0 && y == 0.0 && x.sign() == y.sign())
+                {
+                    expected = -0.0;
+                }
+                check(
+                    "add_rounded",
+                    x,
+                    y,
+                    round,
+                    expected,
+                    add_rounded(x, y, round),
+                );
+            }
+
+            let diff = x - y;
+            let exact = two_sum(x, -y);
+            for &round in ROUNDS.iter() {
+                let mut expected = expected(diff, exact, round);
+                if diff == 0.0
+                    && round == Round::Down
+                    && !(x == 0.0 && y == 0.0 && x.sign() != y.sign())
+                {
+                    expected = -0.0;
+                }
+                check(
+                    "sub_rounded",
+                    x,
+                    y,
+                    round,
+                    expected,
+                    sub_rounded(x, y, round),
+                );
+            }
+
+            let product = x * y;
+            if let Some(exact) = overflowed(product).or_else(|| $mul_err(x, y, product)) {
+                for &round in ROUNDS.iter() {
+                    let expected = expected(product, exact, round);
+                    check(
+                        "mul_rounded",
+                        x,
+                        y,
+                        round,
+                        expected,
+                        mul_rounded(x, y, round),
+                    );
+                }
+            }
+
+            let quotient = x / y;
+            // Division by zero gives an exact infinity.
+            let exact = if y == 0.0 {
+                Some(Ordering::Equal)
+            } else {
+                overflowed(quotient).or_else(|| $div_err(x, y, quotient))
+            };
+            if let Some(exact) = exact {
+                for &round in ROUNDS.iter() {
+                    let expected = expected(quotient, exact, round);
+                    check(
+                        "div_rounded",
+                        x,
+                        y,
+                        round,
+                        expected,
+                        div_rounded(x, y, round),
+                    );
+                }
+            }
+        });
+    };
+}
+
+#[test]
+fn directed_f32() {
+    // `f64` can hold the exact product of two `f32`s, and any difference of it to another `f32`
+    // with the right sign.
+    directed!(
+        f32,
+        |x: f32, y: f32, n: f32| {
+            let err = x as f64 * y as f64 - n as f64;
+            err.partial_cmp(&0.0).or(Some(Ordering::Equal))
+        },
+        |x: f32, y: f32, n: f32| {
+            if !n.is_finite() || !y.is_finite() || y == 0.0 {
+                return Some(Ordering::Equal);
+            }
+            let err = (x as f64 - n as f64 * y as f64) * y.signum() as f64;
+            err.partial_cmp(&0.0)
+        }
+    );
+}
+
+#[test]
+fn directed_f64() {
+    // The residuals of the fused multiply-add are exact as long as they cannot underflow.
+    let tiny = f64::MIN_POSITIVE * 2f64.powi(53);
+    directed!(
+        f64,
+        |x: f64, y: f64, n: f64| {
+            if !n.is_finite() || x == 0.0 || y == 0.0 {
+                Some(Ordering::Equal)
+            } else if n.abs() < tiny {
+                None
+            } else {
+                x.mul_add(y, -n).partial_cmp(&0.0)
+            }
+        },
+        |x: f64, y: f64, n: f64| {
+            if !n.is_finite() || !y.is_finite() || y == 0.0 {
+                Some(Ordering::Equal)
+            } else if n.abs() < f64::MIN_POSITIVE || x.abs() < tiny {
+                None
+            } else {
+                ((-n).mul_add(y, x) * y.signum()).partial_cmp(&0.0)
+            }
+        }
+    );
+}
+
+macro_rules! i_to_f_rounded {
+    ($($from:ident, $into:ident);*;) => {
+        $(
+            fuzz(N, |x: $from| {
+                // Truncate the integer to the precision of the float, which makes the conversion
+                // of the truncated value exact.
+                let negative = x < <$from as Int>::ZERO;
+                let abs = Int::abs_diff(x, <$from as Int>::ZERO) as u128;
+                let drop = (128 - abs.leading_zeros()).saturating_sub(<$into>::MANTISSA_DIGITS);
+                let truncated = abs >> drop << drop;
+                let truncated = if negative {
+                    -(truncated as $into)
+                } else {
+                    truncated as $into
+                };
+                let exact = if truncated == 0.0 || abs == abs >> drop << drop {
+                    Ordering::Equal
+                } else if truncated > 0.0 {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                };
+                for &round in ROUNDS.iter() {
+                    let (n, exact) = if round == Round::Nearest {
+                        (x as $into, Ordering::Equal)
+                    } else {
+                        (truncated, exact)
+                    };
+                    let expected = expected(n, exact, round);
+                    let res: $into = int_to_float_rounded(x, round);
+                    if !Float::eq_repr(expected, res) {
+                        panic!(
+                            "int_to_float_rounded::<{}, {}>({}, {:?}): expected: {:?}, builtins: {:?}",
+                            stringify!($from),
+                            stringify!($into),
+                            x,
+                            round,
+                            expected,
+                            res
+                        );
+                    }
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn int_to_float_directed() {
+    i_to_f_rounded!(
+        i32, f32;
+        i32, f64;
+        u32, f32;
+        u32, f64;
+        i64, f32;
+        i64, f64;
+        u64, f32;
+        u64, f64;
+        i128, f32;
+        i128, f64;
+        u128, f32;
+        u128, f64;
+    );
+}
@@ -0,0 +1,116 @@
+use compiler_builtins::float::{Float, SoftF32, SoftF64};
+use testcrate::*;
+
+macro_rules! soft_ops {
+    ($fX:ident, $soft:ident) => {
+        fuzz_float_2(N, |x: $fX, y: $fX| {
+            let (a, b) = ($soft::from(x), $soft::from(y));
+            let ops: [(&str, $fX, $soft); 4] = [
+                ("+", x + y, a + b),
+                ("-", x - y, a - b),
+                ("*", x * y, a * b),
+                ("/", x / y, a / b),
+            ];
+            for &(op, expected, res) in ops.iter() {
+                if !Float::eq_repr(expected, <$fX>::from(res)) {
+                    panic!(
+                        "{:?} {} {:?}: expected: {:?}, soft: {:?}",
+                        x, op, y, expected, res
+                    );
+                }
+            }
+            if !Float::eq_repr(x.sqrt(), <$fX>::from(a.sqrt())) {
+                panic!(
+                    "sqrt({:?}): expected: {:?}, soft: {:?}",
+                    x,
+                    x.sqrt(),
+                    a.sqrt()
+                );
+            }
+            assert_eq!(a.partial_cmp(&b), x.partial_cmp(&y), "{:?} <=> {:?}", x, y);
+            assert_eq!(a == b, x == y, "{:?} == {:?}", x, y);
+        });
+    };
+}
+
+#[test]
+fn soft_ops() {
+    soft_ops!(f32, SoftF32);
+    soft_ops!(f64, SoftF64);
+}
+
+macro_rules! soft_conv {
+    ($fX:ident, $soft:ident; $($int:ident, $from:ident, $to:ident;)*) => {
+        $(
+            fuzz(N, |i: $int| {
+                let res = $soft::$from(i);
+                if !Float::eq_repr(i as $fX, <$fX>::from(res)) {
+                    panic!("{} as {}: expected: {:?}, soft: {:?}", i, stringify!($fX), i as $fX, res);
+                }
+            });
+            fuzz_float(N, |x: $fX| {
+                assert_eq!($soft::from(x).$to(), x as $int, "{:?} as {}", x, stringify!($int));
+            });
+        )*
+    };
+}
+
+#[test]
+fn soft_conv() {
+    soft_conv!(f32, SoftF32;
+        i32, from_i32, to_i32;
+        u32, from_u32, to_u32;
+        i64, from_i64, to_i64;
+        u64, from_u64, to_u64;
+        i128, from_i128, to_i128;
+        u128, from_u128, to_u128;
+    );
+    soft_conv!(f64, SoftF64;
+        i32, from_i32, to_i32;
+        u32, from_u32, to_u32;
+        i64, from_i64, to_i64;
+        u64, from_u64, to_u64;
+        i128, from_i128, to_i128;
+        u128, from_u128, to_u128;
+    );
+
+    fuzz_float(N, |x: f32| {
+        let res = f64::from(SoftF64::from(SoftF32::from(x)));
+        assert!(Float::eq_repr(x as f64, res), "{:?} as f64", x);
+    });
+    fuzz_float(N, |x: f64| {
+        let res = f32::from(SoftF64::from(x).to_soft_f32());
+        assert!(Float::eq_repr(x as f32, res), "{:?} as f32", x);
+    });
+}
+
+#[test]
+fn soft_const() {
+    // These are evaluated at compile time.
+    const THIRD: SoftF64 = SoftF64::from_i32(1).div(SoftF64::from_i32(3));
+    const TINY: SoftF32 = SoftF32::from_bits(0x0080_0000).mul(SoftF32::from_bits(0x3f00_0001));
+    const SUM: SoftF64 = SoftF64::from_u64(u64::MAX).add(SoftF64::from_i128(-1 << 100));
+    const DIFF: SoftF32 = SoftF32::from_u32(3).sub(SoftF32::from_bits(0x3dcc_cccd));
+    const WIDE: SoftF64 = SoftF32::from_bits(0x0000_0001).to_soft_f64();
+    const INT: i64 = SoftF64::from_bits(0xc3e0_0000_0000_0001).to_i64();
+    const NAN: u32 = SoftF32::from_bits(0x7fc0_0000).neg().to_u32();
+
+    assert_eq!(f64::from(THIRD), 1.0 / 3.0);
+    let min = f32::MIN_POSITIVE;
+    assert_eq!(f32::from(TINY), min * f32::from_bits(0x3f00_0001));
+    assert_eq!(f64::from(SUM), u64::MAX as f64 + (-1i128 << 100) as f64);
+    assert_eq!(f32::from(DIFF), 3.0 - 0.1);
+    assert_eq!(f64::from(WIDE), f32::from_bits(1) as f64);
+    assert_eq!(INT, i64::MIN);
+    assert_eq!(NAN, 0);
+}
+
+#[test]
+fn soft_debug() {
+    assert_eq!(format!("{:?}", SoftF32::from(1.0)), "SoftF32(0x3f800000)");
+    assert_eq!(format!("{:?}", SoftF32::from(-0.0)), "SoftF32(0x80000000)");
+    assert_eq!(
+        format!("{:?}", SoftF64::from(f64::MIN_POSITIVE / 2.0)),
+        "SoftF64(0x0008000000000000)"
+    );
+}
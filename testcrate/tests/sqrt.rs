@@ -0,0 +1,38 @@
+use compiler_builtins::float::sqrt::soft_sqrt;
+use compiler_builtins::float::Float;
+use testcrate::*;
+
+macro_rules! sqrt {
+    ($($fX:ident),*) => {
+        $(
+            fuzz_float(N, |x: $fX| {
+                let expected = x.sqrt();
+                let res: $fX = soft_sqrt(x);
+                // The sign and payload of NaNs do not matter.
+                if !(Float::eq_repr(expected, res) || (expected.is_nan() && res.is_nan())) {
+                    panic!("sqrt({:?}): std: {:?}, builtins: {:?}", x, expected, res);
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn float_sqrt() {
+    sqrt!(f32, f64);
+}
+
+// Takes a few minutes with `--release`, and far too long without.
+#[cfg_attr(debug_assertions, ignore)]
+#[test]
+fn float_sqrt_exhaustive() {
+    // Negative numbers other than -0.0 all give NaN, which `float_sqrt` covers.
+    for bits in 0..=0x8000_0000u32 {
+        let x = f32::from_bits(bits);
+        let expected = x.sqrt();
+        let res: f32 = soft_sqrt(x);
+        if !(Float::eq_repr(expected, res) || (expected.is_nan() && res.is_nan())) {
+            panic!("sqrt({:?}): std: {:?}, builtins: {:?}", x, expected, res);
+        }
+    }
+}
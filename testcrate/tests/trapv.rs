@@ -0,0 +1,135 @@
+use std::env;
+use std::process::Command;
+
+use compiler_builtins::int::trapv::*;
+use testcrate::*;
+
+macro_rules! trapv_2 {
+    ($($i:ty, $checked:ident, $fn:ident);*;) => {
+        $(
+            fuzz_2(N, |x: $i, y: $i| {
+                if let Some(res0) = x.$checked(y) {
+                    let res1: $i = $fn(x, y);
+                    if res0 != res1 {
+                        panic!(
+                            "{}({}, {}): std: {}, builtins: {}",
+                            stringify!($fn), x, y, res0, res1
+                        );
+                    }
+                }
+            });
+        )*
+    };
+}
+
+macro_rules! trapv_1 {
+    ($($i:ty, $checked:ident, $fn:ident);*;) => {
+        $(
+            fuzz(N, |x: $i| {
+                if let Some(res0) = x.$checked() {
+                    let res1: $i = $fn(x);
+                    if res0 != res1 {
+                        panic!(
+                            "{}({}): std: {}, builtins: {}",
+                            stringify!($fn), x, res0, res1
+                        );
+                    }
+                }
+            });
+        )*
+    };
+}
+
+#[test]
+fn trapv() {
+    trapv_2!(
+        i32, checked_add, __addvsi3;
+        i64, checked_add, __addvdi3;
+        i128, checked_add, __addvti3;
+        i32, checked_sub, __subvsi3;
+        i64, checked_sub, __subvdi3;
+        i128, checked_sub, __subvti3;
+        i32, checked_mul, __mulvsi3;
+        i64, checked_mul, __mulvdi3;
+        i128, checked_mul, __mulvti3;
+    );
+    trapv_1!(
+        i32, checked_neg, __negvsi2;
+        i64, checked_neg, __negvdi2;
+        i128, checked_neg, __negvti2;
+        i32, checked_abs, __absvsi2;
+        i64, checked_abs, __absvdi2;
+        i128, checked_abs, __absvti2;
+    );
+}
+
+/// Every entry overflows, and must abort the process
+const OVERFLOWS: &[(&str, fn())] = &[
+    ("__addvsi3", || {
+        __addvsi3(i32::MAX, 1);
+    }),
+    ("__addvdi3", || {
+        __addvdi3(i64::MIN, -1);
+    }),
+    ("__addvti3", || {
+        __addvti3(i128::MAX, i128::MAX);
+    }),
+    ("__subvsi3", || {
+        __subvsi3(i32::MIN, 1);
+    }),
+    ("__subvdi3", || {
+        __subvdi3(0, i64::MIN);
+    }),
+    ("__subvti3", || {
+        __subvti3(i128::MAX, -1);
+    }),
+    ("__mulvsi3", || {
+        __mulvsi3(i32::MIN, -1);
+    }),
+    ("__mulvdi3", || {
+        __mulvdi3(1 << 32, 1 << 31);
+    }),
+    ("__mulvti3", || {
+        __mulvti3(i128::MAX, 2);
+    }),
+    ("__negvsi2", || {
+        __negvsi2(i32::MIN);
+    }),
+    ("__negvdi2", || {
+        __negvdi2(i64::MIN);
+    }),
+    ("__negvti2", || {
+        __negvti2(i128::MIN);
+    }),
+    ("__absvsi2", || {
+        __absvsi2(i32::MIN);
+    }),
+    ("__absvdi2", || {
+        __absvdi2(i64::MIN);
+    }),
+    ("__absvti2", || {
+        __absvti2(i128::MIN);
+    }),
+];
+
+#[test]
+fn trapv_overflow() {
+    // The overflowing calls are made in child processes running only this test, because they
+    // abort.
+    if let Ok(i) = env::var("TRAPV_OVERFLOW_CASE") {
+        OVERFLOWS[i.parse::<usize>().unwrap()].1();
+        return;
+    }
+    for (i, &(name, _)) in OVERFLOWS.iter().enumerate() {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(["--exact", "trapv_overflow", "--test-threads=1"])
+            .env("TRAPV_OVERFLOW_CASE", i.to_string())
+            .output()
+            .unwrap();
+        assert!(
+            !output.status.success(),
+            "{} did not abort on overflow",
+            name
+        );
+    }
+}